@@ -0,0 +1,108 @@
+// Solves every reachable 3x3 position and writes the results out as a lookup table, which
+// src/engine/perfect.rs includes. The crate can't be used from here, so the board is just an
+// array of cells: 0 for empty, 1 for X and 2 for O.
+
+use std::env;
+use std::fs;
+use std::path::Path;
+
+const CELLS : usize = 9;
+// Two entries per board, for X and for O to move
+const ENTRIES : usize = 19683 * 2;
+
+const LINES : [[usize; 3]; 8] = [
+    [0, 1, 2], [3, 4, 5], [6, 7, 8],
+    [0, 3, 6], [1, 4, 7], [2, 5, 8],
+    [0, 4, 8], [2, 4, 6],
+];
+
+// Outcomes as stored in bits 9 and 10 of an entry, where 0 marks an unreachable position
+const WIN : u32 = 1;
+const DRAW : u32 = 2;
+const LOSS : u32 = 3;
+
+fn index(cells: &[u8; CELLS], to_move: u8) -> usize {
+    let code = cells.iter().rev().fold(0, |code, &cell| code * 3 + cell as usize);
+    code * 2 + (to_move == 2) as usize
+}
+
+fn has_line(cells: &[u8; CELLS], token: u8) -> bool {
+    LINES.iter().any(|line| line.iter().all(|&cell| cells[cell] == token))
+}
+
+// Scores an outcome the way the minimax engine does: quick wins best, quick losses worst
+fn score(outcome: u32, distance: u32) -> i32 {
+    match outcome {
+        WIN => 100 - distance as i32,
+        LOSS => distance as i32 - 100,
+        _ => 0,
+    }
+}
+
+fn solve(cells: &mut [u8; CELLS], to_move: u8, table: &mut [u32]) -> (u32, u32) {
+    let index = index(cells, to_move);
+    if table[index] != 0 {
+        return ((table[index] >> 9) & 3, (table[index] >> 11) & 15)
+    }
+
+    let other = 3 - to_move;
+    let (outcome, distance, moves) = if has_line(cells, other) {
+        (LOSS, 0, 0)
+    } else if cells.iter().all(|&cell| cell != 0) {
+        (DRAW, 0, 0)
+    } else {
+        let mut best : Option<(u32, u32)> = None;
+        let mut moves = 0;
+
+        for cell in 0..CELLS {
+            if cells[cell] != 0 {
+                continue
+            }
+
+            cells[cell] = to_move;
+            let (outcome, distance) = solve(cells, other, table);
+            cells[cell] = 0;
+
+            // The reply's outcome turned around, one move further away
+            let value = match outcome {
+                WIN => (LOSS, distance + 1),
+                LOSS => (WIN, distance + 1),
+                _ => (DRAW, distance + 1),
+            };
+
+            let better = best.map(|(outcome, distance)| score(value.0, value.1).cmp(&score(outcome, distance)));
+            match better {
+                None | Some(std::cmp::Ordering::Greater) => {
+                    best = Some(value);
+                    moves = 1 << cell;
+                },
+                Some(std::cmp::Ordering::Equal) => moves |= 1 << cell,
+                Some(std::cmp::Ordering::Less) => (),
+            }
+        }
+
+        let (outcome, distance) = best.expect("an unfinished board has an empty cell");
+        (outcome, distance, moves)
+    };
+
+    table[index] = moves | outcome << 9 | distance << 11;
+    (outcome, distance)
+}
+
+fn main() {
+    let mut table = vec![0; ENTRIES];
+    // Either side may move first
+    solve(&mut [0; CELLS], 1, &mut table);
+    solve(&mut [0; CELLS], 2, &mut table);
+
+    let mut source = format!("static PERFECT_PLAY : [u16; {}] = [\n", ENTRIES);
+    for row in table.chunks(16) {
+        let row : Vec<String> = row.iter().map(|entry| entry.to_string()).collect();
+        source += &format!("    {},\n", row.join(", "));
+    }
+    source += "];\n";
+
+    let path = Path::new(&env::var("OUT_DIR").expect("cargo sets OUT_DIR")).join("perfect_play.rs");
+    fs::write(path, source).expect("the table can be written");
+    println!("cargo:rerun-if-changed=build.rs");
+}
@@ -0,0 +1,369 @@
+use tictactoe::record::{column_name, parse_tile_name, tile_name};
+use tictactoe::selfplay::{play_match, Contender, MatchReport};
+use tictactoe::{Game, GameStatus, Position, Tile, Turn, Winner};
+
+use std::fs;
+use std::io::{self, BufRead, Write};
+
+const USAGE : &str = "\
+Usage:
+  tictactoe                                  play against the engine
+  tictactoe match FIRST SECOND [--games N] [--seed S] [--save FILE]
+                                             play engines against each other
+
+Engines are rule, random, minimax, mcts, table, or a difficulty level: easy, medium, hard
+or perfect. table looks moves up in the precomputed 3x3 table, while perfect is the
+rule-based engine without handicaps. Decisive games are written to FILE as game records,
+one after another, when --save is given.";
+
+const HELP : &str = "\
+Moves can be given as a tile name such as b2, as x and y coordinates from 0 such as 1 1 or 1,1,
+or on a 3x3 board as a numpad digit, where 7 is the top left and 3 the bottom right.
+
+Commands:
+  undo   take back your last move and the engine's reply
+  hint   suggest a move
+  new    start a new game
+  help   show this message
+  quit   leave";
+
+/// Something typed at the move prompt
+#[derive(Debug, PartialEq)]
+enum Command {
+    Move(Position),
+    Undo,
+    Hint,
+    New,
+    Help,
+    Quit,
+}
+
+fn parse_command(input: &str, game: &Game) -> Option<Command> {
+    let input = input.trim().to_ascii_lowercase();
+
+    match input.as_str() {
+        "undo" | "u" => return Some(Command::Undo),
+        "hint" | "h" => return Some(Command::Hint),
+        "new" | "n" => return Some(Command::New),
+        "help" | "?" => return Some(Command::Help),
+        "quit" | "q" | "exit" => return Some(Command::Quit),
+        _ => (),
+    }
+
+    parse_move(&input, game).map(Command::Move)
+}
+
+fn parse_move(input: &str, game: &Game) -> Option<Position> {
+    let numbers : Vec<&str> = input.split(|c: char| c == ',' || c.is_whitespace()).filter(|part| !part.is_empty()).collect();
+
+    match numbers.as_slice() {
+        [digit] if game.board().dimensions() == (3, 3, 3) && digit.len() == 1 => {
+            // Laid out like a numpad, so the top row is 7 8 9
+            let digit = digit.parse::<usize>().ok().filter(|digit| (1..=9).contains(digit))? - 1;
+            Some((digit % 3, 2 - digit / 3))
+        },
+        [name] => parse_tile_name(name),
+        [x, y] => Some((x.parse().ok()?, y.parse().ok()?)),
+        _ => None,
+    }
+}
+
+fn show_board<W: Write>(output: &mut W, game: &Game) -> io::Result<()> {
+    let board = game.board();
+
+    // Wide boards have two letter columns, and every tile is spaced to match
+    let cell = column_name(board.width() - 1).len();
+
+    write!(output, "   ")?;
+    for x in 0..board.width() {
+        write!(output, " {:>cell$}", column_name(x), cell = cell)?;
+    }
+    writeln!(output)?;
+
+    for y in 0..board.height() {
+        write!(output, "{:>3}", y + 1)?;
+        for x in 0..board.width() {
+            match board.tile(x, y) {
+                Some(tile) => write!(output, " {:>cell$}", format!("{:?}", tile), cell = cell)?,
+                None => write!(output, " {:>cell$}", ".", cell = cell)?,
+            }
+        }
+        writeln!(output)?;
+    }
+
+    Ok(())
+}
+
+// Reads a line, or `None` once the input has run out
+fn prompt<R: BufRead, W: Write>(input: &mut R, output: &mut W, message: &str) -> io::Result<Option<String>> {
+    write!(output, "{}", message)?;
+    output.flush()?;
+
+    let mut line = String::new();
+    if input.read_line(&mut line)? == 0 {
+        return Ok(None)
+    }
+
+    Ok(Some(line.trim().to_string()))
+}
+
+// Asks which token the player wants and who should move first
+fn setup<R: BufRead, W: Write>(input: &mut R, output: &mut W) -> io::Result<Option<Game>> {
+    let token = loop {
+        match prompt(input, output, "Play as X or O? ")?.as_deref().map(str::to_ascii_lowercase).as_deref() {
+            Some("x") => break Tile::X,
+            Some("o") => break Tile::O,
+            Some(_) => writeln!(output, "Please answer X or O")?,
+            None => return Ok(None),
+        }
+    };
+
+    let first_turn = loop {
+        match prompt(input, output, "Move first? (y/n) ")?.as_deref().map(str::to_ascii_lowercase).as_deref() {
+            Some("y") | Some("yes") => break Turn::Player,
+            Some("n") | Some("no") => break Turn::AI,
+            Some(_) => writeln!(output, "Please answer y or n")?,
+            None => return Ok(None),
+        }
+    };
+
+    Ok(Some(Game::with_options(token, first_turn)))
+}
+
+fn show_result<W: Write>(output: &mut W, game: &Game) -> io::Result<()> {
+    match game.result() {
+        GameStatus::Won { by: Winner::Player, .. } => writeln!(output, "You win!"),
+        GameStatus::Won { by: Winner::AI, .. } => writeln!(output, "The engine wins."),
+        GameStatus::Draw => writeln!(output, "It's a draw."),
+        GameStatus::InProgress => Ok(()),
+    }
+}
+
+fn run<R: BufRead, W: Write>(input: &mut R, output: &mut W) -> io::Result<()> {
+    writeln!(output, "Tic tac toe. Type help at any time for the commands.")?;
+
+    let mut game = match setup(input, output)? {
+        Some(game) => game,
+        None => return Ok(()),
+    };
+
+    loop {
+        if game.turn() == Some(Turn::AI) {
+            // The engine is only asked while the game is on and it's the engine's turn
+            let explanation = game.ai_move_explained().expect("the engine can always move");
+            writeln!(output, "Engine plays {}: {}", tile_name(explanation.position), explanation)?;
+        }
+
+        writeln!(output)?;
+        show_board(output, &game)?;
+
+        let message = if game.turn().is_some() {
+            format!("Your move ({:?}): ", game.player_token())
+        } else {
+            show_result(output, &game)?;
+            "Type new to play again, undo to take back, or quit: ".to_string()
+        };
+
+        let line = match prompt(input, output, &message)? {
+            Some(line) => line,
+            None => return Ok(()),
+        };
+        if line.is_empty() {
+            continue
+        }
+
+        match parse_command(&line, &game) {
+            Some(Command::Move((x, y))) => {
+                if let Err(error) = game.player_move(x, y) {
+                    writeln!(output, "{}", error)?;
+                }
+            },
+            Some(Command::Undo) => {
+                // Back to the player's last move, taking the engine's reply with it
+                let mut undone = false;
+                while let Some(played) = game.undo() {
+                    undone = true;
+                    if played.turn == Turn::Player {
+                        break
+                    }
+                }
+
+                if !undone {
+                    writeln!(output, "Nothing to undo")?;
+                }
+            },
+            Some(Command::Hint) => match game.suggest_player_move() {
+                Ok(hints) => match hints.first() {
+                    Some(hint) => writeln!(output, "Try {}: {}", tile_name(hint.position), hint)?,
+                    None => writeln!(output, "No moves left")?,
+                },
+                Err(error) => writeln!(output, "{}", error)?,
+            },
+            Some(Command::New) => match setup(input, output)? {
+                Some(new_game) => game = new_game,
+                None => return Ok(()),
+            },
+            Some(Command::Help) => writeln!(output, "{}", HELP)?,
+            Some(Command::Quit) => return Ok(()),
+            None => writeln!(output, "Didn't understand '{}', type help for the commands", line)?,
+        }
+    }
+}
+
+/// The settings for the `match` subcommand
+#[derive(Debug, PartialEq)]
+struct MatchOptions {
+    first: Contender,
+    second: Contender,
+    games: usize,
+    seed: u64,
+    save: Option<String>,
+}
+
+fn parse_match_options(args: &[String]) -> Result<MatchOptions, String> {
+    let mut engines = Vec::new();
+    let mut options = MatchOptions {
+        first: Contender::RuleBased,
+        second: Contender::Random,
+        games: 100,
+        seed: 0,
+        save: None,
+    };
+
+    let mut args = args.iter();
+    while let Some(arg) = args.next() {
+        let mut value = |name: &str| args.next().cloned().ok_or(format!("{} needs a value", name));
+
+        match arg.as_str() {
+            "--games" => options.games = value("--games")?.parse().map_err(|_| "--games needs a number".to_string())?,
+            "--seed" => options.seed = value("--seed")?.parse().map_err(|_| "--seed needs a number".to_string())?,
+            "--save" => options.save = Some(value("--save")?),
+            engine => engines.push(engine.parse::<Contender>()?),
+        }
+    }
+
+    match engines.as_slice() {
+        &[first, second] => {
+            options.first = first;
+            options.second = second;
+            Ok(options)
+        },
+        _ => Err("Expected two engines".to_string()),
+    }
+}
+
+fn save_games(path: &str, report: &MatchReport) -> io::Result<()> {
+    let records : Vec<String> = report.decisive.iter().map(|record| record.to_string()).collect();
+
+    fs::write(path, records.join("\n"))
+}
+
+fn run_match(args: &[String]) -> Result<(), String> {
+    let options = parse_match_options(args)?;
+    let report = play_match(options.first, options.second, options.games, options.seed);
+
+    println!("{}", report);
+    if let Some(path) = &options.save {
+        save_games(path, &report).map_err(|error| format!("Can't save games to {}: {}", path, error))?;
+        println!("Saved {} decisive games to {}", report.decisive.len(), path);
+    }
+
+    Ok(())
+}
+
+fn main() {
+    let args : Vec<String> = std::env::args().skip(1).collect();
+
+    let result = match args.first().map(String::as_str) {
+        None => {
+            let stdin = io::stdin();
+            let stdout = io::stdout();
+            run(&mut stdin.lock(), &mut stdout.lock()).map_err(|error| error.to_string())
+        },
+        Some("match") => run_match(&args[1..]),
+        Some("help") | Some("--help") | Some("-h") => {
+            println!("{}", USAGE);
+            Ok(())
+        },
+        Some(_) => Err(USAGE.to_string()),
+    };
+
+    if let Err(error) = result {
+        eprintln!("{}", error);
+        std::process::exit(1);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use tictactoe::record::Record;
+
+    #[test]
+    fn commands() {
+        let game = Game::new();
+
+        assert_eq!(parse_command("b2", &game), Some(Command::Move((1, 1))));
+        assert_eq!(parse_command("2 0", &game), Some(Command::Move((2, 0))));
+        assert_eq!(parse_command("2,0", &game), Some(Command::Move((2, 0))));
+        assert_eq!(parse_command("7", &game), Some(Command::Move((0, 0))));
+        assert_eq!(parse_command("3", &game), Some(Command::Move((2, 2))));
+        assert_eq!(parse_command(" UNDO ", &game), Some(Command::Undo));
+        assert_eq!(parse_command("hint", &game), Some(Command::Hint));
+        assert_eq!(parse_command("0", &game), None);
+        assert_eq!(parse_command("1 2 3", &game), None);
+        assert_eq!(parse_command("x y", &game), None);
+    }
+
+    #[test]
+    fn scripted_session() {
+        let script = "z\nx\nmaybe\ny\n5\nhint\n99 99\nb2\nnonsense\nundo\nundo\n1\n9\n3\n4\nnew\no\nn\nquit\n";
+        let mut output = Vec::new();
+
+        run(&mut script.as_bytes(), &mut output).unwrap();
+
+        let output = String::from_utf8(output).unwrap();
+        assert!(output.contains("Please answer X or O"));
+        assert!(output.contains("Please answer y or n"));
+        assert!(output.contains("Try c3: (2, 2) takes the corner across from (0, 0)"));
+        assert!(output.contains("Coordinates not found for Tile : (99, 99)"));
+        assert!(output.contains("Tile (1, 1) is already claimed by X"));
+        assert!(output.contains("Didn't understand 'nonsense'"));
+        assert!(output.contains("Nothing to undo"));
+        assert!(output.contains("Engine plays a1: (0, 0) takes an empty corner"));
+        assert!(output.contains("The engine wins.\nType new to play again"));
+        assert!(output.contains("Game is over"));
+        assert!(output.ends_with("Your move (O): "));
+    }
+
+    #[test]
+    fn saved_games_read_back() {
+        let report = play_match(Contender::Level(tictactoe::engine::Difficulty::Easy), Contender::Random, 20, 4);
+        let path = std::env::temp_dir().join(format!("tictactoe-saved-{}.txt", std::process::id()));
+        let path = path.to_str().unwrap();
+
+        save_games(path, &report).unwrap();
+        let read = Record::parse_all(&fs::read_to_string(path).unwrap()).unwrap();
+        fs::remove_file(path).unwrap();
+
+        assert!(report.decisive.len() > 1);
+        assert_eq!(read.iter().map(|record| &record.moves).collect::<Vec<_>>(), report.decisive.iter().map(|record| &record.moves).collect::<Vec<_>>());
+    }
+
+    #[test]
+    fn match_options() {
+        let args = |line: &str| line.split_whitespace().map(String::from).collect::<Vec<_>>();
+
+        assert_eq!(parse_match_options(&args("minimax easy --games 10 --seed 3 --save out.txt")), Ok(MatchOptions {
+            first: Contender::Minimax,
+            second: "easy".parse().unwrap(),
+            games: 10,
+            seed: 3,
+            save: Some("out.txt".to_string()),
+        }));
+        assert_eq!(parse_match_options(&args("rule")), Err("Expected two engines".to_string()));
+        assert_eq!(parse_match_options(&args("rule random --games")), Err("--games needs a value".to_string()));
+        assert_eq!(parse_match_options(&args("rule random --games ten")), Err("--games needs a number".to_string()));
+        assert_eq!(parse_match_options(&args("rule chess")), Err("Unknown engine 'chess'".to_string()));
+    }
+}
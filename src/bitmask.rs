@@ -0,0 +1,107 @@
+use std::ops::{BitAnd, BitAndAssign, BitOr, BitOrAssign, Not};
+
+const WORDS : usize = 4;
+
+/// Largest number of tiles a board can have, one bit per tile
+pub(crate) const MAX_BITS : usize = WORDS * 64;
+
+/// A fixed size set of tile indices
+#[derive(Debug, Default, PartialEq, Eq, Hash, Clone, Copy)]
+pub(crate) struct Bitmask([u64; WORDS]);
+
+impl Bitmask {
+    pub fn empty() -> Bitmask {
+        Bitmask([0; WORDS])
+    }
+
+    /// A mask with the lowest `count` bits set
+    pub fn first(count: usize) -> Bitmask {
+        let mut mask = Bitmask::empty();
+        for (word, bits) in mask.0.iter_mut().enumerate() {
+            let remaining = count.saturating_sub(word * 64);
+            *bits = if remaining >= 64 { !0 } else { (1 << remaining) - 1 };
+        }
+        mask
+    }
+
+    pub fn set(&mut self, index: usize) {
+        self.0[index / 64] |= 1 << (index % 64);
+    }
+
+    pub fn clear(&mut self, index: usize) {
+        self.0[index / 64] &= !(1 << (index % 64));
+    }
+
+    pub fn contains(&self, index: usize) -> bool {
+        self.0[index / 64] & (1 << (index % 64)) != 0
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.0.iter().all(|&bits| bits == 0)
+    }
+
+    pub fn count(&self) -> usize {
+        self.0.iter().map(|bits| bits.count_ones() as usize).sum()
+    }
+
+    /// Every set index, lowest first
+    pub fn indices(&self) -> impl Iterator<Item = usize> + '_ {
+        self.0.iter().enumerate().flat_map(|(word, &bits)| {
+            let mut remaining = bits;
+            std::iter::from_fn(move || {
+                if remaining == 0 {
+                    None
+                } else {
+                    let index = remaining.trailing_zeros() as usize;
+                    remaining &= remaining - 1;
+                    Some(word * 64 + index)
+                }
+            })
+        })
+    }
+}
+
+impl BitAnd for Bitmask {
+    type Output = Bitmask;
+
+    fn bitand(mut self, other: Bitmask) -> Bitmask {
+        self &= other;
+        self
+    }
+}
+
+impl BitAndAssign for Bitmask {
+    fn bitand_assign(&mut self, other: Bitmask) {
+        for (bits, other) in self.0.iter_mut().zip(other.0.iter()) {
+            *bits &= other;
+        }
+    }
+}
+
+impl BitOr for Bitmask {
+    type Output = Bitmask;
+
+    fn bitor(mut self, other: Bitmask) -> Bitmask {
+        self |= other;
+        self
+    }
+}
+
+impl BitOrAssign for Bitmask {
+    fn bitor_assign(&mut self, other: Bitmask) {
+        for (bits, other) in self.0.iter_mut().zip(other.0.iter()) {
+            *bits |= other;
+        }
+    }
+}
+
+impl Not for Bitmask {
+    type Output = Bitmask;
+
+    fn not(mut self) -> Bitmask {
+        for bits in self.0.iter_mut() {
+            *bits = !*bits;
+        }
+        self
+    }
+}
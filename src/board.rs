@@ -1,53 +1,432 @@
+use crate::bitmask::{Bitmask, MAX_BITS};
 use crate::tile::Tile;
+use crate::conditions::{Direction, Line};
+use crate::error::GameError;
+use crate::rng::Rng;
 
-use std::collections::HashMap;
-use std::ops::{Deref, DerefMut};
+use std::fmt;
+use std::hash::{Hash, Hasher};
+use std::sync::Arc;
 
-#[derive(Debug, PartialEq, Clone)]
+#[cfg(feature = "serde")]
+use serde::{Deserialize, Serialize};
+#[cfg(feature = "serde")]
+use std::convert::TryFrom;
+
+/// Board coordinates as `(x, y)`, with `(0, 0)` in the top left corner
+pub type Position = (usize, usize);
+
+// Everything about a board which depends only on its dimensions, shared between copies
+#[derive(Debug)]
+struct Layout {
+    width: usize,
+    height: usize,
+    win_length: usize,
+    // One bit per tile, numbered row by row from the top left corner
+    full: Bitmask,
+    lines: Vec<(Line, Bitmask)>,
+    // A random key for each token on each tile, which the zobrist hash is built from
+    keys: Vec<[u64; 2]>,
+}
+
+impl Layout {
+    fn new(width: usize, height: usize, win_length: usize) -> Layout {
+        let mut lines = Vec::new();
+
+        for &direction in &[Direction::Row, Direction::Column, Direction::MatchDiagonal, Direction::UnmatchDiagonal] {
+            let (step_x, step_y) = direction.step();
+
+            for y in 0..height {
+                for x in 0..width {
+                    // The far end of the line has to stay on the board
+                    let end_x = x as isize + step_x * (win_length as isize - 1);
+                    let end_y = y as isize + step_y * (win_length as isize - 1);
+                    if end_x < 0 || end_x >= width as isize || end_y >= height as isize {
+                        continue
+                    }
+
+                    let line = Line { start: (x, y), direction, length: win_length };
+                    let mut mask = Bitmask::empty();
+                    for (tile_x, tile_y) in line.tiles() {
+                        mask.set(tile_y * width + tile_x);
+                    }
+
+                    lines.push((line, mask));
+                }
+            }
+        }
+
+        Layout {
+            width,
+            height,
+            win_length,
+            full: Bitmask::first(width * height),
+            lines,
+            keys: Layout::zobrist_keys(width, height, win_length),
+        }
+    }
+
+    // Seeded from the dimensions, so every board of the same size agrees on the keys
+    fn zobrist_keys(width: usize, height: usize, win_length: usize) -> Vec<[u64; 2]> {
+        let mut rng = Rng::new(((width as u64) << 32) ^ ((height as u64) << 16) ^ win_length as u64);
+
+        (0..width * height).map(|_| [rng.next_u64(), rng.next_u64()]).collect()
+    }
+}
+
+/// The tiles of a tic tac toe board and the token claiming each of them.
+///
+/// Boards are `width` tiles across and `height` tiles down, and a token wins by claiming
+/// `win_length` tiles in a row, column or diagonal.
+#[derive(Clone)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize), serde(try_from = "BoardData", into = "BoardData"))]
 pub struct Board {
-    inner: HashMap<(usize, usize), Option<Tile>>,
+    layout: Arc<Layout>,
+    x_mask: Bitmask,
+    o_mask: Bitmask,
+    zobrist: u64,
+}
+
+/// The serialized form of a board, with every tile in an array row by row from the top
+/// left corner, as JSON can't key a map by coordinates
+#[cfg(feature = "serde")]
+#[derive(Serialize, Deserialize)]
+pub(crate) struct BoardData {
+    width: usize,
+    height: usize,
+    win_length: usize,
+    tiles: Vec<Option<Tile>>,
+}
+
+#[cfg(feature = "serde")]
+impl From<Board> for BoardData {
+    fn from(board: Board) -> BoardData {
+        let (width, height, win_length) = board.dimensions();
+
+        BoardData {
+            width,
+            height,
+            win_length,
+            tiles: (0..height).flat_map(|y| (0..width).map(move |x| (x, y))).map(|(x, y)| board.tile(x, y)).collect(),
+        }
+    }
 }
 
-impl Deref for Board {
-    type Target = HashMap<(usize, usize), Option<Tile>>;
+#[cfg(feature = "serde")]
+impl TryFrom<BoardData> for Board {
+    type Error = GameError;
+
+    fn try_from(data: BoardData) -> Result<Board, GameError> {
+        let mut board = Board::with_size(data.width, data.height, data.win_length)?;
+        if data.tiles.len() != data.width * data.height {
+            return Err(GameError::InvalidSize { width: data.width, height: data.height, win_length: data.win_length })
+        }
 
-    fn deref(&self) -> &Self::Target {
-        &self.inner
+        for (index, tile) in data.tiles.into_iter().enumerate() {
+            if let Some(tile) = tile {
+                let (x, y) = board.position(index);
+                board.make_move(x, y, tile)?;
+            }
+        }
+
+        Ok(board)
     }
 }
 
-impl DerefMut for Board {
-    fn deref_mut(&mut self) -> &mut Self::Target {
-        &mut self.inner
+impl Default for Board {
+    fn default() -> Self {
+        Board::new()
+    }
+}
+
+impl PartialEq for Board {
+    fn eq(&self, other: &Self) -> bool {
+        self.dimensions() == other.dimensions() && self.x_mask == other.x_mask && self.o_mask == other.o_mask
+    }
+}
+
+impl Eq for Board {}
+
+impl Hash for Board {
+    fn hash<H: Hasher>(&self, state: &mut H) {
+        self.dimensions().hash(state);
+        self.x_mask.hash(state);
+        self.o_mask.hash(state);
+    }
+}
+
+impl fmt::Debug for Board {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(f, "Board({})", self)
     }
 }
 
 impl Board {
+    /// The classic 3x3 board with three in a row to win
     pub fn new() -> Board {
-        let mut board = HashMap::new();
+        Board::with_size(3, 3, 3).expect("3x3 is a valid board")
+    }
 
-        for x in 0..3 {
-            for y in 0..3 {
-                board.insert((x, y), None);
-            }
+    /// A `width` by `height` board where `win_length` in a row wins
+    pub fn with_size(width: usize, height: usize, win_length: usize) -> Result<Board, GameError> {
+        if width == 0 || height == 0 || width * height > MAX_BITS || win_length == 0 || win_length > width.max(height) {
+            return Err(GameError::InvalidSize { width, height, win_length })
         }
 
+        Ok(Board {
+            layout: Arc::new(Layout::new(width, height, win_length)),
+            x_mask: Bitmask::empty(),
+            o_mask: Bitmask::empty(),
+            zobrist: 0,
+        })
+    }
+
+    pub fn width(&self) -> usize {
+        self.layout.width
+    }
+
+    pub fn height(&self) -> usize {
+        self.layout.height
+    }
+
+    /// Number of tiles in a row needed to win
+    pub fn win_length(&self) -> usize {
+        self.layout.win_length
+    }
+
+    /// `(width, height, win_length)`
+    pub fn dimensions(&self) -> (usize, usize, usize) {
+        (self.layout.width, self.layout.height, self.layout.win_length)
+    }
+
+    /// An empty board with the same dimensions
+    pub fn cleared(&self) -> Board {
         Board {
-            inner : board
+            layout: self.layout.clone(),
+            x_mask: Bitmask::empty(),
+            o_mask: Bitmask::empty(),
+            zobrist: 0,
         }
     }
 
-    pub fn make_move(&mut self, x: usize, y: usize, tile: Tile) -> Result<(), String> {
-        if let Some(tile_entry) = self.get_mut(&(x,y)) {
-            if let Some(owner) = tile_entry {
-                Err(format!("Tile ({}, {}) is already claimed by {:?}", x, y, owner))
-            } else {
-                *tile_entry = Some(tile);
-                Ok(())
+    // Whether both boards use the same lines and keys, rather than equal copies of them
+    #[cfg(test)]
+    pub(crate) fn shares_layout(&self, other: &Board) -> bool {
+        Arc::ptr_eq(&self.layout, &other.layout)
+    }
+
+    fn index(&self, x: usize, y: usize) -> Option<usize> {
+        if x < self.width() && y < self.height() {
+            Some(y * self.width() + x)
+        } else {
+            None
+        }
+    }
+
+    fn position(&self, index: usize) -> Position {
+        (index % self.width(), index / self.width())
+    }
+
+    fn positions(&self, mask: Bitmask) -> Vec<Position> {
+        mask.indices().map(|index| self.position(index)).collect()
+    }
+
+    fn mask(&self, tile: Tile) -> Bitmask {
+        match tile {
+            Tile::X => self.x_mask,
+            Tile::O => self.o_mask,
+        }
+    }
+
+    fn filled_mask(&self) -> Bitmask {
+        self.x_mask | self.o_mask
+    }
+
+    /// A hash of the tokens on the board, kept up to date as moves are made and undone rather
+    /// than worked out from scratch. The same tokens on boards of the same size always give
+    /// the same hash, whatever order they were placed in.
+    pub fn zobrist(&self) -> u64 {
+        self.zobrist
+    }
+
+    fn key(&self, index: usize, tile: Tile) -> u64 {
+        match tile {
+            Tile::X => self.layout.keys[index][0],
+            Tile::O => self.layout.keys[index][1],
+        }
+    }
+
+    /// A hash of the dimensions and tokens which is the same on every platform and build,
+    /// for seeding choices from the position
+    pub(crate) fn fingerprint(&self) -> u64 {
+        const FNV_PRIME : u64 = 0x0100_0000_01b3;
+
+        let (width, height, win_length) = self.dimensions();
+        let mut hash : u64 = 0xcbf2_9ce4_8422_2325;
+        let mut add = |value: u64| hash = (hash ^ value).wrapping_mul(FNV_PRIME);
+
+        add(width as u64);
+        add(height as u64);
+        add(win_length as u64);
+        for y in 0..height {
+            for x in 0..width {
+                add(match self.tile(x, y) {
+                    None => 0,
+                    Some(Tile::X) => 1,
+                    Some(Tile::O) => 2,
+                });
             }
+        }
+
+        hash
+    }
+
+    /// Whether `(x, y)` is on the board
+    pub fn contains(&self, x: usize, y: usize) -> bool {
+        self.index(x, y).is_some()
+    }
+
+    /// The token claiming the tile at `(x, y)`, if any
+    pub fn tile(&self, x: usize, y: usize) -> Option<Tile> {
+        let index = self.index(x, y)?;
+
+        if self.x_mask.contains(index) {
+            Some(Tile::X)
+        } else if self.o_mask.contains(index) {
+            Some(Tile::O)
         } else {
-            Err(format!("Coordinates not found for Tile : ({}, {})", x, y))
+            None
+        }
+    }
+
+    /// Whether the tile at `(x, y)` is on the board and unclaimed
+    pub fn is_legal_move(&self, x: usize, y: usize) -> bool {
+        self.index(x, y).is_some_and(|index| !self.filled_mask().contains(index))
+    }
+
+    /// Number of claimed tiles
+    pub fn filled_tiles(&self) -> usize {
+        self.filled_mask().count()
+    }
+
+    /// Number of tiles claimed by `tile`
+    pub fn count(&self, tile: Tile) -> usize {
+        self.mask(tile).count()
+    }
+
+    /// Whether every tile has been claimed
+    pub fn is_full(&self) -> bool {
+        self.filled_mask() == self.layout.full
+    }
+
+    /// Every unclaimed tile, ordered row by row
+    pub fn empty_tiles(&self) -> Vec<Position> {
+        self.positions(!self.filled_mask() & self.layout.full)
+    }
+
+    /// Every line which can be completed to win the game
+    pub fn lines(&self) -> Vec<Line> {
+        self.layout.lines.iter().map(|(line, _)| *line).collect()
+    }
+
+    /// Every line which passes through `(x, y)`
+    pub fn lines_through(&self, x: usize, y: usize) -> Vec<Line> {
+        match self.index(x, y) {
+            Some(index) => self.layout.lines.iter()
+                .filter(|(_, mask)| mask.contains(index))
+                .map(|(line, _)| *line)
+                .collect(),
+            None => Vec::new(),
+        }
+    }
+
+    /// Number of tiles in `line` claimed by `token`, and number left empty
+    pub fn line_counts(&self, line: &Line, token: Tile) -> (usize, usize) {
+        line.tiles().iter().fold((0, 0), |(owned, empty), &(x, y)| {
+            match self.tile(x, y) {
+                Some(tile) if tile == token => (owned + 1, empty),
+                Some(_) => (owned, empty),
+                None => (owned, empty + 1),
+            }
+        })
+    }
+
+    /// The tiles making up `line`
+    pub fn line_tiles(&self, line: &Line) -> Vec<Position> {
+        line.tiles()
+    }
+
+    /// Every empty tile which would complete a line for `token`
+    pub fn threats(&self, token: Tile) -> Vec<Position> {
+        let owned = self.mask(token);
+        let blocked = self.mask(token.other());
+
+        let mut threats = Bitmask::empty();
+        for (_, line) in self.layout.lines.iter() {
+            if (blocked & *line).is_empty() && (owned & *line).count() == self.win_length() - 1 {
+                threats |= *line & !owned;
+            }
+        }
+
+        self.positions(threats)
+    }
+
+    /// For every line, how many of its tiles `token` and the other side have claimed
+    pub(crate) fn line_tallies(&self, token: Tile) -> impl Iterator<Item = (usize, usize)> + '_ {
+        let (owned, other) = (self.mask(token), self.mask(token.other()));
+
+        self.layout.lines.iter().map(move |(_, line)| ((owned & *line).count(), (other & *line).count()))
+    }
+
+    /// Every line `token` has completed
+    pub(crate) fn completed_lines(&self, token: Tile) -> Vec<Line> {
+        let owned = self.mask(token);
+
+        self.layout.lines.iter()
+            .filter(|(_, mask)| owned & *mask == *mask)
+            .map(|(line, _)| *line)
+            .collect()
+    }
+
+    /// The token which has completed a line, and the line it completed
+    pub fn winner(&self) -> Option<(Tile, Line)> {
+        self.layout.lines.iter().find_map(|(line, mask)| {
+            if self.x_mask & *mask == *mask {
+                Some((Tile::X, *line))
+            } else if self.o_mask & *mask == *mask {
+                Some((Tile::O, *line))
+            } else {
+                None
+            }
+        })
+    }
+
+    pub fn make_move(&mut self, x: usize, y: usize, tile: Tile) -> Result<(), GameError> {
+        let index = self.index(x, y).ok_or(GameError::OutOfBounds { x, y })?;
+
+        if let Some(owner) = self.tile(x, y) {
+            return Err(GameError::Occupied { x, y, by: owner })
         }
+
+        match tile {
+            Tile::X => self.x_mask.set(index),
+            Tile::O => self.o_mask.set(index),
+        }
+        self.zobrist ^= self.key(index, tile);
+
+        Ok(())
+    }
+
+    /// Empties the tile at `(x, y)`, returning the token which had claimed it
+    pub fn undo_move(&mut self, x: usize, y: usize) -> Option<Tile> {
+        let index = self.index(x, y)?;
+        let owner = self.tile(x, y)?;
+
+        self.x_mask.clear(index);
+        self.o_mask.clear(index);
+        self.zobrist ^= self.key(index, owner);
+
+        Some(owner)
     }
 }
 
@@ -59,21 +438,99 @@ mod tests {
 
         let board = Board::new();
 
-        if board.keys().len() != 9 {
+        if board.empty_tiles().len() != 9 {
             panic!("Board does not have 9 elements")
         }
 
-        for (k, v) in board.iter() {
-            let (x, y) = k;
-
-            if *x > 2 || *y > 2 {
-                panic!("Board is larger than 3x3")
-            }
+        for x in 0..3 {
+            for y in 0..3 {
+                if !board.contains(x, y) {
+                    panic!("Board is smaller than 3x3")
+                }
 
-            if *v != None {
-                panic!("Tile is not starting empty")
+                if board.tile(x, y).is_some() {
+                    panic!("Tile is not starting empty")
+                }
             }
+        }
 
+        if board.contains(3, 0) || board.contains(0, 3) {
+            panic!("Board is larger than 3x3")
         }
+
+        assert_eq!(board.lines().len(), 8);
+    }
+
+    #[test]
+    fn make_move_errors() {
+        use super::*;
+
+        let mut board = Board::new();
+
+        board.make_move(1, 1, Tile::X).unwrap();
+        assert_eq!(board.make_move(1, 1, Tile::O), Err(GameError::Occupied { x: 1, y: 1, by: Tile::X }));
+        assert_eq!(board.make_move(3, 0, Tile::O), Err(GameError::OutOfBounds { x: 3, y: 0 }));
+
+        assert_eq!(board.undo_move(1, 1), Some(Tile::X));
+        assert_eq!(board.undo_move(1, 1), None);
+        assert_eq!(board.undo_move(3, 0), None);
+        board.make_move(1, 1, Tile::O).unwrap();
+        assert_eq!(board.tile(1, 1), Some(Tile::O));
+    }
+
+    #[test]
+    fn threats_and_winner() {
+        use super::*;
+
+        let mut board = Board::new();
+
+        board.make_move(0, 0, Tile::X).unwrap();
+        board.make_move(1, 1, Tile::X).unwrap();
+        board.make_move(2, 0, Tile::O).unwrap();
+        assert_eq!(board.threats(Tile::X), vec![(2, 2)]);
+        assert_eq!(board.winner(), None);
+
+        board.make_move(2, 2, Tile::X).unwrap();
+        assert_eq!(board.winner(), Some((Tile::X, Line { start: (0, 0), direction: Direction::MatchDiagonal, length: 3 })));
+        assert!(!board.is_legal_move(2, 2));
+        assert_eq!(format!("{:?}", board), "Board(X.O/.X./..X)");
+    }
+
+    #[test]
+    fn larger_boards() {
+        use super::*;
+
+        // 4 rows and 4 columns of 4, plus both long diagonals
+        assert_eq!(Board::with_size(4, 4, 4).unwrap().lines().len(), 10);
+        // 11 starting tiles in each of the 15 rows and columns, and 11x11 for each diagonal
+        assert_eq!(Board::with_size(15, 15, 5).unwrap().lines().len(), 2 * 15 * 11 + 2 * 11 * 11);
+        assert_eq!(Board::with_size(3, 3, 4), Err(GameError::InvalidSize { width: 3, height: 3, win_length: 4 }));
+        assert!(Board::with_size(17, 17, 5).is_err());
+
+        let mut board = Board::with_size(5, 4, 3).unwrap();
+        board.make_move(4, 0, Tile::O).unwrap();
+        board.make_move(3, 1, Tile::O).unwrap();
+        assert_eq!(board.threats(Tile::O), vec![(2, 2)]);
+
+        board.make_move(2, 2, Tile::O).unwrap();
+        assert_eq!(board.winner(), Some((Tile::O, Line { start: (4, 0), direction: Direction::UnmatchDiagonal, length: 3 })));
+        assert_eq!(board.line_tiles(&board.winner().unwrap().1), vec![(4, 0), (3, 1), (2, 2)]);
+    }
+
+    #[cfg(feature = "serde")]
+    #[test]
+    fn serde_json() {
+        use super::*;
+
+        let mut board = Board::with_size(3, 2, 2).unwrap();
+        board.make_move(0, 0, Tile::X).unwrap();
+        board.make_move(2, 1, Tile::O).unwrap();
+
+        let json = serde_json::to_string(&board).unwrap();
+        assert_eq!(json, r#"{"width":3,"height":2,"win_length":2,"tiles":["X",null,null,null,null,"O"]}"#);
+        assert_eq!(serde_json::from_str::<Board>(&json).unwrap(), board);
+
+        assert!(serde_json::from_str::<Board>(r#"{"width":3,"height":2,"win_length":2,"tiles":["X"]}"#).is_err());
+        assert!(serde_json::from_str::<Board>(r#"{"width":3,"height":2,"win_length":4,"tiles":[]}"#).is_err());
     }
 }
@@ -1,28 +1,93 @@
-use crate::tile::Tile;
-use crate::board::Board;
+use crate::board::Position;
 
-#[derive(Debug, Hash, PartialEq, PartialOrd, Eq)]
+#[cfg(feature = "serde")]
+use serde::{Deserialize, Serialize};
+
+/// The way a line runs across the board
+#[derive(Debug, Hash, PartialEq, PartialOrd, Eq, Copy, Clone)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
 pub enum Direction {
-    Row(usize),
-    Column(usize),
+    /// Left to right
+    Row,
+    /// Top to bottom
+    Column,
+    /// Top left to bottom right
     MatchDiagonal,
+    /// Top right to bottom left
     UnmatchDiagonal
 }
 
+impl Direction {
+    /// How far `(x, y)` moves between neighbouring tiles of the line
+    pub fn step(self) -> (isize, isize) {
+        match self {
+            Direction::Row => (1, 0),
+            Direction::Column => (0, 1),
+            Direction::MatchDiagonal => (1, 1),
+            Direction::UnmatchDiagonal => (-1, 1),
+        }
+    }
+}
+
+/// A run of tiles which wins the game when claimed by a single token
+#[derive(Debug, Hash, PartialEq, Eq, Copy, Clone)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
+pub struct Line {
+    /// The first tile of the line, which is its top end (or left end for a row)
+    pub start: Position,
+    pub direction: Direction,
+    pub length: usize,
+}
+
+impl Line {
+    /// The tiles making up the line, in order from `start`
+    pub fn tiles(&self) -> Vec<Position> {
+        let (step_x, step_y) = self.direction.step();
+
+        (0..self.length as isize).map(|index| {
+            ((self.start.0 as isize + step_x * index) as usize, (self.start.1 as isize + step_y * index) as usize)
+        }).collect()
+    }
+}
+
+#[cfg(test)]
 #[derive(Debug, PartialEq)]
 pub enum CheckType {
     Win,
     Block
 }
 
-#[derive(Debug, PartialEq)]
-pub enum EmptyTile {
-    Corner,
-    Side
+/// The side which completed a line
+#[derive(Debug, PartialEq, Copy, Clone)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
+pub enum Winner {
+    AI,
+    Player
 }
 
-#[derive(Debug, PartialEq)]
-pub enum Winner {
+/// The side which is expected to move next
+#[derive(Debug, PartialEq, Copy, Clone)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
+pub enum Turn {
     AI,
     Player
-}
\ No newline at end of file
+}
+
+impl Turn {
+    /// The side moving after this one
+    pub fn other(self) -> Turn {
+        match self {
+            Turn::AI => Turn::Player,
+            Turn::Player => Turn::AI,
+        }
+    }
+}
+
+/// Where a game currently stands
+#[derive(Debug, PartialEq, Copy, Clone)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
+pub enum GameStatus {
+    InProgress,
+    Won { by: Winner, line: Line },
+    Draw
+}
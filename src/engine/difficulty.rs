@@ -0,0 +1,209 @@
+use crate::board::Position;
+use crate::engine::{Engine, RuleBased, TieBreak};
+use crate::error::GameError;
+use crate::game::Game;
+use crate::rng::Rng;
+use crate::strategy::Strategy;
+
+/// How well an engine plays, from making plenty of mistakes to never losing
+#[derive(Debug, PartialEq, Eq, Hash, Copy, Clone)]
+pub enum Difficulty {
+    Easy,
+    Medium,
+    Hard,
+    Perfect,
+}
+
+impl Difficulty {
+    /// Every level, easiest first
+    pub const ALL : [Difficulty; 4] = [
+        Difficulty::Easy,
+        Difficulty::Medium,
+        Difficulty::Hard,
+        Difficulty::Perfect,
+    ];
+
+    /// The mistakes an engine makes at this level
+    pub fn handicap(self) -> Handicap {
+        match self {
+            Difficulty::Easy => Handicap {
+                skipped: vec![Strategy::Fork, Strategy::BlockFork, Strategy::OppositeCorner],
+                blunder_rate: 0.4,
+            },
+            Difficulty::Medium => Handicap {
+                skipped: vec![Strategy::BlockFork],
+                blunder_rate: 0.15,
+            },
+            Difficulty::Hard => Handicap {
+                skipped: Vec::new(),
+                blunder_rate: 0.05,
+            },
+            Difficulty::Perfect => Handicap::default(),
+        }
+    }
+}
+
+/// The ways an engine falls short of perfect play
+#[derive(Debug, Default, PartialEq, Clone)]
+pub struct Handicap {
+    /// Stages of the rule-based engine which are never tried
+    pub skipped: Vec<Strategy>,
+    /// The chance of ignoring every strategy and claiming a random empty tile instead, so
+    /// 1.0 always plays randomly
+    pub blunder_rate: f64,
+}
+
+/// The rule-based engine playing with a handicap.
+///
+/// Every random choice depends only on `seed` and the board, so the same seed always plays
+/// the same move in the same position.
+///
+/// ```
+/// use tictactoe::{Game, Tile, Turn};
+/// use tictactoe::engine::{Difficulty, Handicapped};
+///
+/// let mut game = Game::with_options(Tile::X, Turn::AI);
+/// let first = game.clone().ai_move_with(&mut Handicapped::new(Difficulty::Easy, 42));
+///
+/// assert_eq!(game.ai_move_with(&mut Handicapped::new(Difficulty::Easy, 42)), first);
+/// ```
+#[derive(Debug)]
+pub struct Handicapped {
+    handicap: Handicap,
+    seed: u64,
+    engine: RuleBased,
+}
+
+impl Handicapped {
+    /// Plays at `difficulty`, with `seed` deciding every random choice
+    pub fn new(difficulty: Difficulty, seed: u64) -> Self {
+        Handicapped::with_handicap(difficulty.handicap(), seed)
+    }
+
+    /// Plays with a custom `handicap`, with `seed` deciding every random choice
+    pub fn with_handicap(handicap: Handicap, seed: u64) -> Self {
+        let engine = handicap.skipped.iter()
+            .fold(RuleBased::with_tie_break(TieBreak::Random { seed }), |engine, &strategy| engine.without(strategy));
+
+        Handicapped {
+            handicap,
+            seed,
+            engine,
+        }
+    }
+
+    /// Claims a uniformly random empty tile every move
+    pub fn random(seed: u64) -> Self {
+        Handicapped::with_handicap(Handicap { skipped: Vec::new(), blunder_rate: 1.0 }, seed)
+    }
+
+    pub fn handicap(&self) -> &Handicap {
+        &self.handicap
+    }
+}
+
+impl Engine for Handicapped {
+    fn choose_move(&mut self, game: &Game) -> Result<Position, GameError> {
+        self.choose_move_and_strategy(game).map(|(position, _)| position)
+    }
+
+    fn choose_move_and_strategy(&mut self, game: &Game) -> Result<(Position, Option<Strategy>), GameError> {
+        if game.turn().is_none() {
+            return Err(GameError::GameOver)
+        }
+
+        // Rotated so blunders don't follow the same stream as the tie break
+        let mut rng = Rng::new(self.seed.rotate_left(32) ^ game.board().fingerprint());
+        if rng.chance(self.handicap.blunder_rate) {
+            let empty = game.board().empty_tiles();
+            return Ok((empty[rng.below(empty.len())], None))
+        }
+
+        self.engine.choose_move_and_strategy(game)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::conditions::{GameStatus, Turn, Winner};
+
+    // Plays a whole game between `ai` and `player`, returning how it ended
+    fn play<A: super::Engine, P: super::Engine>(ai: &mut A, player: &mut P, first_turn: Turn) -> GameStatus {
+        use super::*;
+        use crate::tile::Tile;
+
+        let mut game = Game::with_options(Tile::O, first_turn);
+        while let Some(turn) = game.turn() {
+            match turn {
+                Turn::AI => {
+                    game.ai_move_with(ai).unwrap();
+                },
+                Turn::Player => {
+                    let (x, y) = player.choose_move(&game).unwrap();
+                    game.player_move(x, y).unwrap();
+                },
+            }
+        }
+
+        game.result()
+    }
+
+    #[test]
+    fn perfect_never_loses() {
+        use super::*;
+
+        for seed in 0..50 {
+            for &first_turn in &[Turn::AI, Turn::Player] {
+                let result = play(&mut Handicapped::new(Difficulty::Perfect, seed), &mut Handicapped::random(seed), first_turn);
+                assert!(!matches!(result, GameStatus::Won { by: Winner::Player, .. }), "seed {} lost", seed);
+            }
+        }
+    }
+
+    #[test]
+    fn easier_levels_lose_more() {
+        use super::*;
+
+        let losses : Vec<usize> = Difficulty::ALL.iter().map(|&difficulty| {
+            (0..100).filter(|&seed| {
+                let first_turn = if seed % 2 == 0 { Turn::AI } else { Turn::Player };
+                let result = play(&mut Handicapped::new(difficulty, seed), &mut RuleBased::new(), first_turn);
+                matches!(result, GameStatus::Won { by: Winner::Player, .. })
+            }).count()
+        }).collect();
+
+        assert!(losses[0] > losses[1] && losses[1] > losses[2], "losses by level: {:?}", losses);
+        assert_eq!(losses[3], 0);
+    }
+
+    #[test]
+    fn skipping_the_fallbacks_still_moves() {
+        use super::*;
+
+        let handicap = Handicap { skipped: vec![Strategy::EmptyCorner, Strategy::EmptySide], blunder_rate: 0.0 };
+        let mut engine = Handicapped::with_handicap(handicap, 1);
+
+        // Nothing but the fallbacks would play here: the center is gone and there's no line to finish or block
+        let mut game = Game::new();
+        game.player_move(1, 1).unwrap();
+        let (x, y) = engine.choose_move(&game).unwrap();
+        assert!(game.board().is_legal_move(x, y));
+
+        // And a whole game can be played out
+        let result = play(&mut engine, &mut Handicapped::random(1), Turn::Player);
+        assert!(result != GameStatus::InProgress);
+    }
+
+    #[test]
+    fn random_mover_only_plays_legal_tiles() {
+        use super::*;
+
+        let mut random = Handicapped::random(3);
+        let mut game = Game::new();
+        game.player_move(1, 1).unwrap();
+
+        let (x, y) = random.choose_move(&game).unwrap();
+        assert!(game.board().is_legal_move(x, y));
+        assert_eq!(random.choose_move(&game), Ok((x, y)));
+    }
+}
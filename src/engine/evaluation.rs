@@ -0,0 +1,97 @@
+use crate::board::{Board, Position};
+use crate::engine::Value;
+use crate::tile::Tile;
+
+use std::fmt;
+
+/// The value of claiming each tile of a board, for the side to move
+#[derive(Debug, Clone)]
+pub struct Evaluation {
+    board: Board,
+    token: Tile,
+    values: Vec<Option<Value>>,
+}
+
+impl Evaluation {
+    pub(crate) fn new(board: &Board, token: Tile) -> Evaluation {
+        Evaluation {
+            board: board.clone(),
+            token,
+            values: vec![None; board.width() * board.height()],
+        }
+    }
+
+    pub(crate) fn set(&mut self, x: usize, y: usize, value: Value) {
+        let width = self.board.width();
+        self.values[y * width + x] = Some(value);
+    }
+
+    /// The side the values are for
+    pub fn token(&self) -> Tile {
+        self.token
+    }
+
+    /// The position which was evaluated
+    pub fn board(&self) -> &Board {
+        &self.board
+    }
+
+    /// The value of claiming `(x, y)`, or `None` if it can't be claimed
+    pub fn value(&self, x: usize, y: usize) -> Option<Value> {
+        if self.board.contains(x, y) {
+            self.values[y * self.board.width() + x]
+        } else {
+            None
+        }
+    }
+
+    /// Every tile which can be claimed with its value, row by row
+    pub fn moves(&self) -> Vec<(Position, Value)> {
+        let width = self.board.width();
+
+        self.values.iter().enumerate()
+            .filter_map(|(index, value)| value.map(|value| ((index % width, index / width), value)))
+            .collect()
+    }
+
+    /// The value of the position, which is that of its best move, or `None` once the game is over
+    pub fn best(&self) -> Option<Value> {
+        self.values.iter().flatten().max().copied()
+    }
+
+    /// Every tile which reaches the value of the position
+    pub fn best_moves(&self) -> Vec<Position> {
+        match self.best() {
+            Some(best) => self.moves().into_iter().filter(|&(_, value)| value == best).map(|(position, _)| position).collect(),
+            None => Vec::new(),
+        }
+    }
+}
+
+/// Draws the board as a grid, with claimed tiles shown by their token and empty tiles by
+/// `W`, `D` or `L` followed by the distance to the end of the game
+impl fmt::Display for Evaluation {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        for y in 0..self.board.height() {
+            if y > 0 {
+                writeln!(f)?;
+            }
+
+            let mut row = String::new();
+            for x in 0..self.board.width() {
+                let cell = match (self.board.tile(x, y), self.value(x, y)) {
+                    (Some(tile), _) => format!("{:?}", tile),
+                    (None, Some(Value::Win(distance))) => format!("W{}", distance),
+                    (None, Some(Value::Draw)) => "D".to_string(),
+                    (None, Some(Value::Loss(distance))) => format!("L{}", distance),
+                    (None, None) => ".".to_string(),
+                };
+
+                row.push_str(&format!("{:<4}", cell));
+            }
+            write!(f, "{}", row.trim_end())?;
+        }
+
+        Ok(())
+    }
+}
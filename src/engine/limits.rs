@@ -0,0 +1,69 @@
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::sync::Arc;
+use std::time::{Duration, Instant};
+
+/// Stops a search from another thread. Clones share the same flag, so one clone can be handed
+/// to the search while another is kept to cancel it.
+#[derive(Debug, Default, Clone)]
+pub struct CancelToken {
+    cancelled: Arc<AtomicBool>,
+}
+
+impl CancelToken {
+    pub fn new() -> Self {
+        CancelToken::default()
+    }
+
+    pub fn cancel(&self) {
+        self.cancelled.store(true, Ordering::Relaxed);
+    }
+
+    pub fn is_cancelled(&self) -> bool {
+        self.cancelled.load(Ordering::Relaxed)
+    }
+}
+
+/// When a search should give up and settle for the best move it has found so far. A search
+/// stops at whichever limit it reaches first, and with no limits at all it runs until the
+/// game is solved.
+#[derive(Debug, Default, Clone)]
+pub struct Limits {
+    /// The time to stop by
+    pub deadline: Option<Instant>,
+    /// How long each search may run, counted from when it starts
+    pub time: Option<Duration>,
+    /// Positions which may be visited
+    pub nodes: Option<usize>,
+    pub cancel: Option<CancelToken>,
+}
+
+// The limits of one search, with its deadline worked out
+#[derive(Debug, Clone)]
+pub(crate) struct Stop {
+    deadline: Option<Instant>,
+    nodes: Option<usize>,
+    cancel: Option<CancelToken>,
+}
+
+impl Stop {
+    pub fn new(limits: &Limits) -> Stop {
+        let timed = limits.time.map(|time| Instant::now() + time);
+
+        Stop {
+            deadline: match (limits.deadline, timed) {
+                (Some(deadline), Some(timed)) => Some(deadline.min(timed)),
+                (deadline, timed) => deadline.or(timed),
+            },
+            nodes: limits.nodes,
+            cancel: limits.cancel.clone(),
+        }
+    }
+
+    /// Whether the search should stop, having visited `nodes` positions
+    pub fn reached(&self, nodes: usize) -> bool {
+        self.nodes.is_some_and(|limit| nodes >= limit)
+            || self.cancel.as_ref().is_some_and(CancelToken::is_cancelled)
+            // Reading the clock costs more than the rest, so it's only checked now and then
+            || (nodes % 64 == 0 && self.deadline.is_some_and(|deadline| Instant::now() >= deadline))
+    }
+}
@@ -0,0 +1,407 @@
+use crate::board::{Board, Position};
+use crate::engine::Engine;
+use crate::engine::limits::{Limits, Stop};
+use crate::error::GameError;
+use crate::game::Game;
+use crate::rng::Rng;
+use crate::tile::Tile;
+
+use std::time::{Duration, Instant};
+
+/// How long a tree search may run for each move
+#[derive(Debug, PartialEq, Eq, Copy, Clone)]
+pub enum Budget {
+    /// Playouts per move
+    Iterations(usize),
+    /// Wall clock time per move, checked between playouts
+    Time(Duration),
+}
+
+/// The exploration constant used unless another is given, the usual square root of two
+pub const DEFAULT_EXPLORATION : f64 = std::f64::consts::SQRT_2;
+
+// One position in the tree, reached by `position`
+#[derive(Debug, Clone)]
+struct Node {
+    position: Option<Position>,
+    /// The side which claimed `position`, and which `reward` is counted for
+    mover: Tile,
+    children: Vec<usize>,
+    untried: Vec<Position>,
+    visits: u32,
+    /// A win scores 1 and a draw half that
+    reward: f64,
+}
+
+impl Node {
+    fn new(position: Option<Position>, mover: Tile, board: &Board) -> Node {
+        let untried = if board.winner().is_some() { Vec::new() } else { board.empty_tiles() };
+
+        Node {
+            position,
+            mover,
+            children: Vec::new(),
+            untried,
+            visits: 0,
+            reward: 0.0,
+        }
+    }
+}
+
+// The nodes searched so far, below the position `board` with `to_move` to move
+#[derive(Debug, Clone)]
+struct Tree {
+    board: Board,
+    to_move: Tile,
+    nodes: Vec<Node>,
+}
+
+impl Tree {
+    fn new(board: &Board, to_move: Tile) -> Tree {
+        Tree {
+            board: board.clone(),
+            to_move,
+            nodes: vec![Node::new(None, to_move.other(), board)],
+        }
+    }
+
+    // The part of the tree below `board`, if it's this position or follows from it by moves
+    // which have already been searched
+    fn descend(mut self, board: &Board, to_move: Tile) -> Option<Tree> {
+        let mut current = 0;
+
+        while self.board != *board || self.to_move != to_move {
+            if self.board.filled_tiles() >= board.filled_tiles() {
+                return None
+            }
+
+            let token = self.to_move;
+            let child = self.nodes[current].children.iter().copied().find(|&child| {
+                let (x, y) = self.nodes[child].position.expect("only the root has no move");
+                self.board.tile(x, y).is_none() && board.tile(x, y) == Some(token)
+            })?;
+
+            let (x, y) = self.nodes[child].position.expect("only the root has no move");
+            let _ = self.board.make_move(x, y, token);
+            self.to_move = token.other();
+            current = child;
+        }
+
+        self.nodes = subtree(&self.nodes, current);
+        Some(self)
+    }
+}
+
+// A copy of the nodes below `root`, with `root` first
+fn subtree(nodes: &[Node], root: usize) -> Vec<Node> {
+    let mut copied = vec![nodes[root].clone()];
+    let mut next = 0;
+
+    while next < copied.len() {
+        let children = std::mem::take(&mut copied[next].children);
+        for child in children {
+            let index = copied.len();
+            copied[next].children.push(index);
+            copied.push(nodes[child].clone());
+        }
+        next += 1;
+    }
+
+    copied
+}
+
+/// Monte Carlo tree search with the UCT selection rule, for boards too large to search
+/// exhaustively.
+///
+/// Each playout follows the most promising moves down the tree, adds one new position and
+/// then plays randomly to the end of the game. The move played is the one visited most. The
+/// tree is kept between moves, so when the next position follows on from the last one the
+/// search carries on where it left off.
+///
+/// The budget bounds every search, and `Limits` can stop one sooner: at a deadline, after a
+/// number of playouts, or when it's cancelled from another thread.
+///
+/// ```
+/// use tictactoe::{Board, Game, Tile, Turn};
+/// use tictactoe::engine::{Budget, Engine, Mcts};
+///
+/// let board : Board = "X...O/.X.../..X../...../O....:4".parse().unwrap();
+/// let mut game = Game::with_board(board, Tile::O, Turn::Player);
+///
+/// let mut mcts = Mcts::new(7).with_budget(Budget::Iterations(2000));
+/// // O has to block the diagonal
+/// assert_eq!(mcts.choose_move(&game), Ok((3, 3)));
+/// assert!(mcts.visits().iter().all(|&(position, visits)| position == (3, 3) || visits < 2000 / 4));
+/// ```
+#[derive(Debug, Clone)]
+pub struct Mcts {
+    budget: Budget,
+    exploration: f64,
+    rng: Rng,
+    limits: Limits,
+    tree: Option<Tree>,
+}
+
+impl Mcts {
+    /// Runs 5000 playouts a move, with `seed` deciding every random choice
+    pub fn new(seed: u64) -> Self {
+        Mcts {
+            budget: Budget::Iterations(5000),
+            exploration: DEFAULT_EXPLORATION,
+            rng: Rng::new(seed),
+            limits: Limits::default(),
+            tree: None,
+        }
+    }
+
+    pub fn with_budget(mut self, budget: Budget) -> Self {
+        self.budget = budget;
+        self
+    }
+
+    /// Stops each search within `limits` as well as the budget, counting each playout as one
+    /// position visited
+    pub fn with_limits(mut self, limits: Limits) -> Self {
+        self.limits = limits;
+        self
+    }
+
+    /// Higher values spend more playouts on moves which have looked poor so far
+    pub fn with_exploration(mut self, exploration: f64) -> Self {
+        self.exploration = exploration;
+        self
+    }
+
+    pub fn budget(&self) -> Budget {
+        self.budget
+    }
+
+    pub fn exploration(&self) -> f64 {
+        self.exploration
+    }
+
+    /// How many playouts went through each move from the last position searched
+    pub fn visits(&self) -> Vec<(Position, u32)> {
+        match &self.tree {
+            Some(tree) => tree.nodes[0].children.iter()
+                .map(|&child| (tree.nodes[child].position.expect("only the root has no move"), tree.nodes[child].visits))
+                .collect(),
+            None => Vec::new(),
+        }
+    }
+
+    /// Runs the budget's playouts from `board` with `token` to move, reusing the tree when
+    /// it can, and returns the most visited move
+    pub fn search(&mut self, board: &Board, token: Tile) -> Option<Position> {
+        if board.winner().is_some() || board.is_full() {
+            return None
+        }
+
+        let mut tree = match self.tree.take().and_then(|tree| tree.descend(board, token)) {
+            Some(tree) => tree,
+            None => Tree::new(board, token),
+        };
+
+        let started = Instant::now();
+        let stop = Stop::new(&self.limits);
+        let mut iterations = 0;
+        // Always one playout, so there is a move to return
+        while iterations == 0 || (!stop.reached(iterations) && match self.budget {
+            Budget::Iterations(limit) => iterations < limit,
+            Budget::Time(limit) => started.elapsed() < limit,
+        }) {
+            self.playout(&mut tree);
+            iterations += 1;
+        }
+
+        let best = tree.nodes[0].children.iter()
+            .max_by_key(|&&child| tree.nodes[child].visits)
+            .and_then(|&child| tree.nodes[child].position);
+
+        self.tree = Some(tree);
+        best
+    }
+
+    fn playout(&mut self, tree: &mut Tree) {
+        let mut board = tree.board.clone();
+        let mut token = tree.to_move;
+        let mut path = vec![0];
+        let mut current = 0;
+
+        // Follow the best moves while every move from the position has been tried
+        while tree.nodes[current].untried.is_empty() && !tree.nodes[current].children.is_empty() {
+            current = self.select(tree, current);
+            let (x, y) = tree.nodes[current].position.expect("only the root has no move");
+            let _ = board.make_move(x, y, token);
+            token = token.other();
+            path.push(current);
+        }
+
+        // Add one new position below it
+        let untried = &mut tree.nodes[current].untried;
+        if !untried.is_empty() {
+            let (x, y) = untried.swap_remove(self.rng.below(untried.len()));
+            let _ = board.make_move(x, y, token);
+
+            let child = tree.nodes.len();
+            tree.nodes.push(Node::new(Some((x, y)), token, &board));
+            tree.nodes[current].children.push(child);
+            token = token.other();
+            path.push(child);
+        }
+
+        // Then play randomly to the end
+        let winner = loop {
+            if let Some((winner, _)) = board.winner() {
+                break Some(winner)
+            }
+
+            let empty = board.empty_tiles();
+            if empty.is_empty() {
+                break None
+            }
+
+            let (x, y) = empty[self.rng.below(empty.len())];
+            let _ = board.make_move(x, y, token);
+            token = token.other();
+        };
+
+        for index in path {
+            let node = &mut tree.nodes[index];
+            node.visits += 1;
+            node.reward += match winner {
+                Some(winner) if winner == node.mover => 1.0,
+                Some(_) => 0.0,
+                None => 0.5,
+            };
+        }
+    }
+
+    // The child of `parent` with the highest upper confidence bound
+    fn select(&self, tree: &Tree, parent: usize) -> usize {
+        let log_visits = (tree.nodes[parent].visits as f64).ln();
+        let bound = |child: usize| {
+            let node = &tree.nodes[child];
+            node.reward / node.visits as f64 + self.exploration * (log_visits / node.visits as f64).sqrt()
+        };
+
+        tree.nodes[parent].children.iter().copied()
+            .fold(None, |best: Option<(usize, f64)>, child| {
+                let value = bound(child);
+                match best {
+                    Some((_, best_value)) if best_value >= value => best,
+                    _ => Some((child, value)),
+                }
+            })
+            .map(|(child, _)| child)
+            .expect("only called on nodes with children")
+    }
+}
+
+impl Engine for Mcts {
+    fn choose_move(&mut self, game: &Game) -> Result<Position, GameError> {
+        let token = game.token_to_move().ok_or(GameError::GameOver)?;
+
+        self.search(game.board(), token).ok_or(GameError::NoMoveAvailable)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    #[test]
+    fn takes_wins_and_blocks() {
+        use super::*;
+
+        let mut mcts = Mcts::new(1).with_budget(Budget::Iterations(1000));
+        let board : Board = "XX./OO./...".parse().unwrap();
+        assert_eq!(mcts.search(&board, Tile::X), Some((2, 0)));
+        assert_eq!(mcts.search(&board, Tile::O), Some((2, 1)));
+
+        let board : Board = "X../.O./..X".parse().unwrap();
+        // Every move was tried and the playouts add up
+        let visits = mcts.visits();
+        mcts.search(&board, Tile::O);
+        assert_ne!(mcts.visits(), visits);
+        assert_eq!(mcts.visits().len(), 6);
+        assert_eq!(mcts.visits().iter().map(|&(_, visits)| visits).sum::<u32>(), 1000);
+
+        assert_eq!(mcts.search(&"XXX/OO./...".parse().unwrap(), Tile::O), None);
+    }
+
+    #[test]
+    fn tree_is_reused() {
+        use super::*;
+
+        let mut mcts = Mcts::new(3).with_budget(Budget::Iterations(500));
+        let mut board = Board::new();
+
+        let (x, y) = mcts.search(&board, Tile::X).unwrap();
+        board.make_move(x, y, Tile::X).unwrap();
+        assert!(mcts.visits().iter().any(|&(position, visits)| position == (x, y) && visits > 0));
+
+        // The search below the chosen move carries on, so it has more than one budget of playouts
+        let (x, y) = board.empty_tiles()[0];
+        board.make_move(x, y, Tile::O).unwrap();
+        mcts.search(&board, Tile::X).unwrap();
+        let total : u32 = mcts.visits().iter().map(|&(_, visits)| visits).sum();
+        assert!(total > 500, "{}", total);
+
+        // A position which doesn't follow starts a new tree
+        mcts.search(&Board::new(), Tile::O).unwrap();
+        assert_eq!(mcts.visits().iter().map(|&(_, visits)| visits).sum::<u32>(), 500);
+    }
+
+    #[test]
+    fn seeded_and_timed() {
+        use super::*;
+
+        let board = Board::with_size(5, 5, 4).unwrap();
+        let first = Mcts::new(11).with_budget(Budget::Iterations(300));
+        let (mut one, mut two) = (first.clone(), first);
+        assert_eq!(one.search(&board, Tile::X), two.search(&board, Tile::X));
+        assert_eq!(one.visits(), two.visits());
+
+        let mut timed = Mcts::new(11).with_budget(Budget::Time(Duration::from_millis(20))).with_exploration(0.5);
+        let started = Instant::now();
+        assert!(timed.search(&board, Tile::X).is_some());
+        assert!(started.elapsed() < Duration::from_secs(1));
+
+        // Even without any time there is always one playout
+        let mut rushed = Mcts::new(11).with_budget(Budget::Time(Duration::from_secs(0)));
+        assert!(rushed.search(&board, Tile::X).is_some());
+    }
+
+    #[test]
+    fn stops_within_limits() {
+        use super::*;
+        use crate::engine::CancelToken;
+
+        let board = Board::with_size(7, 7, 4).unwrap();
+        let long = Budget::Time(Duration::from_secs(60));
+
+        let mut counted = Mcts::new(5).with_budget(long).with_limits(Limits { nodes: Some(200), ..Limits::default() });
+        assert!(counted.search(&board, Tile::X).is_some());
+        assert_eq!(counted.visits().iter().map(|&(_, visits)| visits).sum::<u32>(), 200);
+
+        let started = Instant::now();
+        let mut timed = Mcts::new(5).with_budget(long).with_limits(Limits { time: Some(Duration::from_millis(20)), ..Limits::default() });
+        assert!(timed.search(&board, Tile::X).is_some());
+        assert!(started.elapsed() < Duration::from_secs(5));
+
+        // Cancelled from another thread while the budget still has most of a minute to run
+        let cancel = CancelToken::new();
+        let limits = Limits { cancel: Some(cancel.clone()), ..Limits::default() };
+        let search = std::thread::spawn(move || Mcts::new(5).with_budget(long).with_limits(limits).search(&board, Tile::X));
+        std::thread::sleep(Duration::from_millis(20));
+        cancel.cancel();
+        assert!(search.join().unwrap().is_some());
+        assert!(started.elapsed() < Duration::from_secs(10));
+
+        // Even when cancelled before it starts there is one playout
+        let cancel = CancelToken::new();
+        cancel.cancel();
+        let mut cancelled = Mcts::new(5).with_limits(Limits { cancel: Some(cancel), ..Limits::default() });
+        assert!(cancelled.search(&Board::with_size(7, 7, 4).unwrap(), Tile::X).is_some());
+        assert_eq!(cancelled.visits().iter().map(|&(_, visits)| visits).sum::<u32>(), 1);
+    }
+}
@@ -0,0 +1,624 @@
+use crate::bitmask::MAX_BITS;
+use crate::board::{Board, Position};
+use crate::engine::limits::Stop;
+use crate::engine::{Bound, Engine, Entry, Evaluation, Limits, TranspositionTable};
+use crate::error::GameError;
+use crate::game::Game;
+use crate::tile::Tile;
+
+use std::cmp::Ordering;
+
+// Scores are offset from this by the number of moves played, so quicker wins score higher
+const WIN_SCORE : i32 = 1000;
+// Anything further from zero than this is a proven win or loss, however long the game
+const PROVEN : i32 = WIN_SCORE - MAX_BITS as i32 - 1;
+// Guesses at positions the search didn't reach the end of stay well clear of proven scores
+const MAX_GUESS : i32 = PROVEN / 2;
+
+/// The game-theoretic value of a position for the side to move, assuming perfect play
+/// from both sides. Distances count moves from the position until the game ends.
+#[derive(Debug, PartialEq, Eq, Clone, Copy)]
+pub enum Value {
+    Win(usize),
+    Draw,
+    Loss(usize),
+}
+
+impl Value {
+    fn from_score(score: i32) -> Value {
+        match score.cmp(&0) {
+            Ordering::Greater => Value::Win((WIN_SCORE - score) as usize),
+            Ordering::Less => Value::Loss((WIN_SCORE + score) as usize),
+            Ordering::Equal => Value::Draw,
+        }
+    }
+
+    /// The value of the position one move earlier, for the side which made that move
+    pub fn parent(self) -> Value {
+        match self {
+            Value::Win(distance) => Value::Loss(distance + 1),
+            Value::Draw => Value::Draw,
+            Value::Loss(distance) => Value::Win(distance + 1),
+        }
+    }
+
+    fn score(self) -> i32 {
+        match self {
+            Value::Win(distance) => WIN_SCORE - distance as i32,
+            Value::Draw => 0,
+            Value::Loss(distance) => -(WIN_SCORE - distance as i32),
+        }
+    }
+}
+
+impl PartialOrd for Value {
+    fn partial_cmp(&self, other: &Self) -> Option<Ordering> {
+        Some(self.cmp(other))
+    }
+}
+
+/// Better values compare greater: quick wins over slow wins over draws over slow losses
+/// over quick losses
+impl Ord for Value {
+    fn cmp(&self, other: &Self) -> Ordering {
+        self.score().cmp(&other.score())
+    }
+}
+
+/// A negamax search with alpha-beta pruning, remembering positions it has already searched
+/// in a transposition table which is kept between searches.
+///
+/// It searches to the end of the game unless it's given limits, in which case it deepens
+/// one move at a time and plays the best move from the deepest search it finished. `search`
+/// and `evaluate` never take limits, so a caller which can't wait should use `search_within`.
+#[derive(Debug, Default, Clone)]
+pub struct Minimax {
+    nodes: usize,
+    table: TranspositionTable,
+    limits: Option<Limits>,
+    stop: Option<Stop>,
+    stopped: bool,
+    // Cleared whenever a search has to guess at a position rather than play it out
+    exhaustive: bool,
+}
+
+/// The result of a search with limits
+#[derive(Debug, PartialEq, Eq, Copy, Clone)]
+pub struct Deepened {
+    pub position: Position,
+    /// The value of the position, once the search has got far enough to prove it
+    pub value: Option<Value>,
+    /// Moves ahead searched by the deepest search which finished, where 0 means none did
+    /// and `position` is just the first empty tile
+    pub depth: usize,
+    /// Whether a limit was reached before the game was solved
+    pub interrupted: bool,
+}
+
+impl Minimax {
+    pub fn new() -> Self {
+        Minimax::default()
+    }
+
+    /// Searches with `table`, which may already hold positions from earlier searches
+    pub fn with_table(table: TranspositionTable) -> Self {
+        Minimax {
+            table,
+            ..Minimax::default()
+        }
+    }
+
+    /// Deepens iteratively within `limits` whenever it's asked for a move as an engine
+    pub fn with_limits(mut self, limits: Limits) -> Self {
+        self.limits = Some(limits);
+        self
+    }
+
+    /// Number of positions visited by the last search
+    pub fn nodes(&self) -> usize {
+        self.nodes
+    }
+
+    /// The positions remembered from earlier searches, which later searches start from
+    pub fn table(&self) -> &TranspositionTable {
+        &self.table
+    }
+
+    /// Gives up the table, so another engine can carry on with it
+    pub fn into_table(self) -> TranspositionTable {
+        self.table
+    }
+
+    /// The best tile for `token` to claim on `board` and the value it leads to, or `None`
+    /// if the game is already over. The search runs to the end of the game whatever limits
+    /// the engine was given.
+    pub fn search(&mut self, board: &Board, token: Tile) -> Option<(Position, Value)> {
+        self.nodes = 0;
+
+        if board.winner().is_some() {
+            return None
+        }
+
+        let mut board = board.clone();
+        let moves = board.empty_tiles();
+
+        self.stop = None;
+        self.stopped = false;
+        self.search_root(&mut board, token, &moves, usize::MAX)
+            .map(|(position, score)| (position, Value::from_score(score)))
+    }
+
+    /// Searches one move deeper at a time until the game is solved or a limit in `limits` is
+    /// reached, and returns the best move from the deepest search which finished. The search
+    /// can only be as quick to stop as the limits it's given, so a caller which can't wait
+    /// should always set a deadline, a node limit or a cancel token.
+    ///
+    /// ```
+    /// use std::time::Duration;
+    /// use tictactoe::{Board, Tile};
+    /// use tictactoe::engine::{Limits, Minimax};
+    ///
+    /// let board = Board::with_size(7, 7, 4).unwrap();
+    /// let limits = Limits { time: Some(Duration::from_millis(50)), ..Limits::default() };
+    ///
+    /// let result = Minimax::new().search_within(&board, Tile::X, &limits).unwrap();
+    /// assert!(result.interrupted);
+    /// assert!(board.is_legal_move(result.position.0, result.position.1));
+    /// ```
+    pub fn search_within(&mut self, board: &Board, token: Tile, limits: &Limits) -> Option<Deepened> {
+        self.nodes = 0;
+
+        let mut board = board.clone();
+        let mut moves = board.empty_tiles();
+        if board.winner().is_some() || moves.is_empty() {
+            return None
+        }
+
+        self.stop = Some(Stop::new(limits));
+        self.stopped = false;
+        let mut result = Deepened {
+            position: moves[0],
+            value: None,
+            depth: 0,
+            interrupted: true,
+        };
+
+        for draft in 1..=moves.len() {
+            self.exhaustive = true;
+            // A search cut short is thrown away, as its best move may not have been compared with the rest
+            let (position, score) = match self.search_root(&mut board, token, &moves, draft) {
+                Some(best) => best,
+                None => break,
+            };
+
+            result.position = position;
+            result.depth = draft;
+
+            if self.exhaustive || score.abs() > PROVEN {
+                result.value = Some(Value::from_score(score));
+                result.interrupted = false;
+                break
+            }
+
+            // The best move so far is searched first next time
+            moves.retain(|&tile| tile != position);
+            moves.insert(0, position);
+        }
+
+        self.stop = None;
+        Some(result)
+    }
+
+    /// The value of every tile `token` could claim on `board`, found with a full search
+    /// below each of them. Nothing can be claimed once the game is over. There is no limit on
+    /// the search, so this is only meant for small boards.
+    pub fn evaluate(&mut self, board: &Board, token: Tile) -> Evaluation {
+        self.nodes = 0;
+
+        let mut evaluation = Evaluation::new(board, token);
+        if board.winner().is_some() {
+            return evaluation
+        }
+
+        self.stop = None;
+        self.stopped = false;
+        let mut board = board.clone();
+        for (x, y) in board.empty_tiles() {
+            let _ = board.make_move(x, y, token);
+            // A full window so every tile gets its exact value, not just a bound
+            let score = -self.negamax(&mut board, token.other(), 1, usize::MAX, -WIN_SCORE, WIN_SCORE);
+            board.undo_move(x, y);
+
+            evaluation.set(x, y, Value::from_score(score));
+        }
+
+        evaluation
+    }
+
+    // The best of `moves` and its score, searching `draft` moves ahead, or `None` if the
+    // search was stopped
+    fn search_root(&mut self, board: &mut Board, token: Tile, moves: &[Position], draft: usize) -> Option<(Position, i32)> {
+        let mut best : Option<(Position, i32)> = None;
+        let mut alpha = -WIN_SCORE;
+
+        for &(x, y) in moves {
+            let _ = board.make_move(x, y, token);
+            let score = -self.negamax(board, token.other(), 1, draft - 1, -WIN_SCORE, -alpha);
+            board.undo_move(x, y);
+
+            if self.stopped {
+                return None
+            }
+
+            let better = match best {
+                Some((_, best_score)) => score > best_score,
+                None => true,
+            };
+            if better {
+                best = Some(((x, y), score));
+                alpha = alpha.max(score);
+            }
+        }
+
+        best
+    }
+
+    fn negamax(&mut self, board: &mut Board, token: Tile, depth: i32, draft: usize, mut alpha: i32, mut beta: i32) -> i32 {
+        self.nodes += 1;
+
+        if !self.stopped {
+            self.stopped = self.stop.as_ref().is_some_and(|stop| stop.reached(self.nodes));
+        }
+        if self.stopped {
+            return 0
+        }
+
+        // Only the side which just moved can have completed a line
+        if board.winner().is_some() {
+            return -(WIN_SCORE - depth)
+        }
+
+        let mut empty = board.empty_tiles();
+        if empty.is_empty() {
+            return 0
+        }
+
+        if draft == 0 {
+            self.exhaustive = false;
+            return guess(board, token)
+        }
+
+        // Searching as far as the tiles left covers the whole game
+        let remaining = empty.len();
+        let draft = draft.min(remaining);
+        let key = TranspositionTable::key(board, token);
+        let original_alpha = alpha;
+
+        if let Some(entry) = self.table.probe(key).copied() {
+            if entry.depth >= draft {
+                if entry.depth < remaining {
+                    self.exhaustive = false;
+                }
+
+                let score = from_table(entry.score, depth);
+                match entry.bound {
+                    Bound::Exact => return score,
+                    Bound::Lower => alpha = alpha.max(score),
+                    Bound::Upper => beta = beta.min(score),
+                }
+
+                if alpha >= beta {
+                    return score
+                }
+            }
+
+            // The best move last time is the most likely to cut the search short again
+            if let Some(index) = entry.best.and_then(|best| empty.iter().position(|&tile| tile == best)) {
+                let best = empty.remove(index);
+                empty.insert(0, best);
+            }
+        }
+
+        let mut best = -WIN_SCORE;
+        let mut best_move = None;
+        for (x, y) in empty {
+            let _ = board.make_move(x, y, token);
+            let score = -self.negamax(board, token.other(), depth + 1, draft - 1, -beta, -alpha);
+            board.undo_move(x, y);
+
+            if self.stopped {
+                return 0
+            }
+
+            if score > best {
+                best = score;
+                best_move = Some((x, y));
+            }
+            alpha = alpha.max(score);
+
+            if alpha >= beta {
+                break
+            }
+        }
+
+        let bound = if best <= original_alpha {
+            Bound::Upper
+        } else if best >= beta {
+            Bound::Lower
+        } else {
+            Bound::Exact
+        };
+        self.table.store(Entry { key, depth: draft, score: to_table(best, depth), bound, best: best_move });
+
+        best
+    }
+}
+
+// A guess at how good an unfinished position is for `token`, from how far each side has got
+// with the lines the other hasn't blocked
+fn guess(board: &Board, token: Tile) -> i32 {
+    let score : i32 = board.line_tallies(token)
+        .map(|tally| match tally {
+            (owned, 0) => (owned * owned) as i32,
+            (0, other) => -((other * other) as i32),
+            _ => 0,
+        })
+        .sum();
+
+    score.clamp(-MAX_GUESS, MAX_GUESS)
+}
+
+// Scores count the distance to a win or loss from the root of the search, but a position can
+// be reached at different depths, so the table keeps them counted from the position itself
+fn to_table(score: i32, depth: i32) -> i32 {
+    match score {
+        score if score > PROVEN => score + depth,
+        score if score < -PROVEN => score - depth,
+        score => score,
+    }
+}
+
+fn from_table(score: i32, depth: i32) -> i32 {
+    match score {
+        score if score > PROVEN => score - depth,
+        score if score < -PROVEN => score + depth,
+        score => score,
+    }
+}
+
+impl Engine for Minimax {
+    fn choose_move(&mut self, game: &Game) -> Result<Position, GameError> {
+        let token = game.token_to_move().ok_or(GameError::GameOver)?;
+
+        let position = match self.limits.clone() {
+            Some(limits) => self.search_within(game.board(), token, &limits).map(|result| result.position),
+            None => self.search(game.board(), token).map(|(position, _)| position),
+        };
+        position.ok_or(GameError::NoMoveAvailable)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::conditions::Turn;
+    use crate::engine::RuleBased;
+
+    #[test]
+    fn empty_board_is_a_draw() {
+        use super::*;
+
+        let mut minimax = Minimax::new();
+
+        if let Some((_, value)) = minimax.search(&Board::new(), Tile::X) {
+            assert_eq!(value, Value::Draw);
+        } else {
+            panic!()
+        }
+    }
+
+    #[test]
+    fn takes_the_quickest_win() {
+        use super::*;
+
+        let mut board = Board::new();
+        board.make_move(0, 0, Tile::X).unwrap();
+        board.make_move(1, 0, Tile::X).unwrap();
+        board.make_move(0, 1, Tile::O).unwrap();
+        board.make_move(1, 1, Tile::O).unwrap();
+
+        let mut minimax = Minimax::new();
+        assert_eq!(minimax.search(&board, Tile::X), Some(((2, 0), Value::Win(1))));
+        assert_eq!(minimax.search(&board, Tile::O), Some(((2, 1), Value::Win(1))));
+    }
+
+    #[test]
+    fn sees_a_forced_loss() {
+        use super::*;
+
+        // X holds opposite corners with O on a side, so X can fork no matter what O does
+        let mut board = Board::new();
+        board.make_move(0, 0, Tile::X).unwrap();
+        board.make_move(1, 0, Tile::O).unwrap();
+        board.make_move(1, 1, Tile::X).unwrap();
+
+        let mut minimax = Minimax::new();
+        if let Some((position, value)) = minimax.search(&board, Tile::O) {
+            assert_eq!(position, (2, 2));
+            assert_eq!(value, Value::Loss(4));
+        } else {
+            panic!()
+        }
+    }
+
+    #[test]
+    fn evaluates_every_tile() {
+        use super::*;
+
+        // O to move must block at (2, 0), anything else loses to X completing the row
+        let mut board = Board::new();
+        board.make_move(0, 0, Tile::X).unwrap();
+        board.make_move(1, 1, Tile::O).unwrap();
+        board.make_move(1, 0, Tile::X).unwrap();
+
+        let mut minimax = Minimax::new();
+        let evaluation = minimax.evaluate(&board, Tile::O);
+
+        assert_eq!(evaluation.token(), Tile::O);
+        assert_eq!(evaluation.value(2, 0), Some(Value::Draw));
+        assert_eq!(evaluation.value(0, 2), Some(Value::Loss(2)));
+        assert_eq!(evaluation.value(0, 0), None);
+        assert_eq!(evaluation.value(5, 5), None);
+        assert_eq!(evaluation.moves().len(), 6);
+        assert_eq!(evaluation.best(), Some(Value::Draw));
+        assert_eq!(evaluation.best_moves(), vec![(2, 0)]);
+        assert_eq!(evaluation.to_string(), "X   X   D\nL2  O   L2\nL2  L2  L2");
+
+        // The same tiles seen by X, who wins at once by completing the row
+        let evaluation = minimax.evaluate(&board, Tile::X);
+        assert_eq!(evaluation.value(2, 0), Some(Value::Win(1)));
+        assert_eq!(minimax.search(&board, Tile::X).map(|(_, value)| value), evaluation.best());
+
+        board.make_move(2, 0, Tile::X).unwrap();
+        assert_eq!(minimax.evaluate(&board, Tile::O).best(), None);
+    }
+
+    #[test]
+    fn table_is_reused() {
+        use super::*;
+        use crate::engine::TranspositionTable;
+
+        let mut without_table = Minimax::with_table(TranspositionTable::new(0));
+        let mut minimax = Minimax::new();
+
+        let expected = without_table.search(&Board::new(), Tile::X);
+        assert_eq!(minimax.search(&Board::new(), Tile::X), expected);
+        assert!(minimax.nodes() < without_table.nodes() / 2, "{} vs {}", minimax.nodes(), without_table.nodes());
+        assert!(!minimax.table().is_empty());
+
+        // A second search starts from what the first one stored
+        let first = minimax.nodes();
+        assert_eq!(minimax.search(&Board::new(), Tile::X), expected);
+        assert!(minimax.nodes() < first);
+
+        // The table can be handed on, and the evaluations it gives are unchanged
+        let mut board = Board::new();
+        board.make_move(0, 0, Tile::X).unwrap();
+        let mut next = Minimax::with_table(minimax.into_table());
+        let fresh = without_table.evaluate(&board, Tile::O);
+        assert_eq!(next.evaluate(&board, Tile::O).moves(), fresh.moves());
+    }
+
+    #[test]
+    fn deepens_within_limits() {
+        use super::*;
+        use crate::engine::{CancelToken, Limits};
+
+        // Without limits it solves the game like a full search
+        let result = Minimax::new().search_within(&Board::new(), Tile::X, &Limits::default()).unwrap();
+        assert_eq!(result.value, Some(Value::Draw));
+        assert!(!result.interrupted);
+
+        let big = Board::with_size(7, 7, 4).unwrap();
+        let mut minimax = Minimax::new();
+        let result = minimax.search_within(&big, Tile::X, &Limits { nodes: Some(20_000), ..Limits::default() }).unwrap();
+        assert!(result.interrupted && result.depth > 0 && result.value.is_none());
+        assert!(minimax.nodes() <= 20_000);
+
+        // Proven wins end the search early
+        let mut threat = big.clone();
+        for x in 1..4 {
+            threat.make_move(x, 3, Tile::X).unwrap();
+            threat.make_move(x, 0, Tile::O).unwrap();
+        }
+        let result = minimax.search_within(&threat, Tile::X, &Limits { nodes: Some(20_000), ..Limits::default() }).unwrap();
+        assert_eq!(result.value, Some(Value::Win(1)));
+        assert!(result.position == (0, 3) || result.position == (4, 3));
+        assert!(!result.interrupted);
+
+        // Cancelled before it starts, it still has a move to give
+        let cancel = CancelToken::new();
+        cancel.cancel();
+        let result = minimax.search_within(&big, Tile::X, &Limits { cancel: Some(cancel), ..Limits::default() }).unwrap();
+        assert_eq!((result.position, result.depth, result.interrupted), ((0, 0), 0, true));
+
+        // The limits don't carry over to searches without them
+        assert_eq!(minimax.search(&Board::new(), Tile::X).map(|(_, value)| value), Some(Value::Draw));
+    }
+
+    #[test]
+    fn cancelled_from_another_thread() {
+        use super::*;
+        use crate::engine::{CancelToken, Limits};
+        use std::time::{Duration, Instant};
+
+        let cancel = CancelToken::new();
+        let limits = Limits { cancel: Some(cancel.clone()), ..Limits::default() };
+        let started = Instant::now();
+
+        let search = std::thread::spawn(move || {
+            Minimax::new().search_within(&Board::with_size(7, 7, 4).unwrap(), Tile::X, &limits)
+        });
+        std::thread::sleep(Duration::from_millis(50));
+        cancel.cancel();
+
+        let result = search.join().unwrap().unwrap();
+        assert!(result.interrupted);
+        assert!(started.elapsed() < Duration::from_secs(5));
+
+        // As an engine the limits apply to every move
+        let mut game = Game::with_board(Board::with_size(7, 7, 4).unwrap(), Tile::O, Turn::AI);
+        let mut engine = Minimax::new().with_limits(Limits { nodes: Some(5_000), ..Limits::default() });
+        let (x, y) = engine.choose_move(&game).unwrap();
+        game.ai_move_at(x, y).unwrap();
+        assert!(engine.nodes() <= 5_000);
+    }
+
+    fn check_rule_based(game: &super::Game, minimax: &mut super::Minimax) {
+        use super::*;
+
+        let token = match game.token_to_move() {
+            Some(token) => token,
+            None => return,
+        };
+
+        if game.turn() == Some(Turn::AI) {
+            let (_, best) = minimax.search(game.board(), token).unwrap();
+
+            let mut next = game.clone();
+            let position = next.ai_move_with(&mut RuleBased::new()).unwrap();
+            let chosen = match minimax.search(next.board(), token.other()) {
+                Some((_, value)) => value.parent(),
+                None if next.board().winner().is_some() => Value::Win(1),
+                None => Value::Draw,
+            };
+
+            // The rule-based engine doesn't always take the quickest line, but it must reach the same outcome
+            assert_eq!(chosen.score().signum(), best.score().signum(), "{:?} played {:?} on {:?}", token, position, game.board());
+
+            check_rule_based(&next, minimax);
+        } else {
+            for (x, y) in game.board().empty_tiles() {
+                let mut next = game.clone();
+                next.player_move(x, y).unwrap();
+                check_rule_based(&next, minimax);
+            }
+        }
+    }
+
+    #[test]
+    fn rule_based_matches_ground_truth() {
+        use super::*;
+
+        let mut minimax = Minimax::new();
+        for &player_token in &[Tile::X, Tile::O] {
+            for &first_turn in &[Turn::AI, Turn::Player] {
+                check_rule_based(&Game::with_options(player_token, first_turn), &mut minimax);
+            }
+        }
+
+        // Sanity check the engine interface itself
+        let mut game = Game::new();
+        game.player_move(0, 0).unwrap();
+        assert_eq!(minimax.choose_move(&game), Ok((1, 1)));
+    }
+}
@@ -0,0 +1,145 @@
+mod difficulty;
+mod evaluation;
+mod limits;
+mod mcts;
+mod minimax;
+mod perfect;
+mod tiebreak;
+mod transposition;
+
+pub use self::difficulty::{Difficulty, Handicap, Handicapped};
+pub use self::evaluation::Evaluation;
+pub use self::mcts::{Budget, Mcts, DEFAULT_EXPLORATION};
+pub use self::limits::{CancelToken, Limits};
+pub use self::minimax::{Deepened, Minimax, Value};
+pub use self::perfect::{perfect_play, Perfect, Solution};
+pub use self::tiebreak::TieBreak;
+pub use self::transposition::{Bound, Entry, TranspositionTable, DEFAULT_CAPACITY};
+
+use crate::board::Position;
+use crate::conditions::Turn;
+use crate::error::GameError;
+use crate::game::{Game, GameState};
+use crate::explain::Explanation;
+use crate::observer::{Event, Observer};
+use crate::strategy::Strategy;
+
+use std::fmt;
+
+/// Anything which can choose a move in a game.
+///
+/// The rule-based engines and `Perfect` only ever look a move or two ahead, so they always
+/// answer quickly. `Mcts` stops at its budget, or sooner within its limits. `Minimax` only
+/// stops early when it's given limits: without them `choose_move`, `Minimax::search` and
+/// `Minimax::evaluate` run until the game is solved, which on larger boards can take longer
+/// than anyone will wait.
+pub trait Engine {
+    /// Chooses a tile for the side expected to move next in `game`
+    fn choose_move(&mut self, game: &Game) -> Result<Position, GameError>;
+
+    /// Chooses a tile like `choose_move`, along with the stage of the rule-based engine which
+    /// picked it, for engines which have one
+    fn choose_move_and_strategy(&mut self, game: &Game) -> Result<(Position, Option<Strategy>), GameError> {
+        self.choose_move(game).map(|position| (position, None))
+    }
+}
+
+impl<E: Engine + ?Sized> Engine for Box<E> {
+    fn choose_move(&mut self, game: &Game) -> Result<Position, GameError> {
+        (**self).choose_move(game)
+    }
+
+    fn choose_move_and_strategy(&mut self, game: &Game) -> Result<(Position, Option<Strategy>), GameError> {
+        (**self).choose_move_and_strategy(game)
+    }
+}
+
+/// The hand-coded strategy, working through win, block, fork, block fork, center,
+/// opposite corner, empty corner and empty side in that order
+#[derive(Default)]
+pub struct RuleBased {
+    tie_break: Option<TieBreak>,
+    skipped: Vec<Strategy>,
+    observer: Option<Box<dyn Observer>>,
+}
+
+impl fmt::Debug for RuleBased {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        f.debug_struct("RuleBased")
+            .field("tie_break", &self.tie_break)
+            .field("skipped", &self.skipped)
+            .field("observed", &self.observer.is_some())
+            .finish()
+    }
+}
+
+impl RuleBased {
+    /// Breaks ties the same way as the game being played
+    pub fn new() -> Self {
+        RuleBased::default()
+    }
+
+    /// Breaks ties with `tie_break`, whatever the game being played uses
+    pub fn with_tie_break(tie_break: TieBreak) -> Self {
+        RuleBased {
+            tie_break: Some(tie_break),
+            ..RuleBased::default()
+        }
+    }
+
+    /// Never tries `strategy`, falling through to the stages after it instead. Skipped empty
+    /// corner and empty side stages still play when no other stage finds a move, so the
+    /// engine always has a move while a tile is empty.
+    pub fn without(mut self, strategy: Strategy) -> Self {
+        self.skipped.push(strategy);
+        self
+    }
+
+    /// Reports every stage checked and move chosen to `observer`
+    pub fn with_observer<O: Observer + 'static>(mut self, observer: O) -> Self {
+        self.observer = Some(Box::new(observer));
+        self
+    }
+
+    /// The tile this engine would claim for the side expected to move next in `game`, along
+    /// with the strategy that picked it and the evidence for it
+    pub fn explain_move(&mut self, game: &Game) -> Result<Explanation, GameError> {
+        let state = self.state_to_move(game)?;
+
+        match &mut self.observer {
+            Some(observer) => state.explain_ai_move(observer.as_mut()),
+            None => state.explain_ai_move(&mut |_: &Event| ()),
+        }
+    }
+
+    // The game seen from the side expected to move next, with this engine's settings
+    fn state_to_move(&self, game: &Game) -> Result<GameState, GameError> {
+        let mut state = match game.turn() {
+            None => return Err(GameError::GameOver),
+            Some(Turn::AI) => game.state().clone(),
+            Some(Turn::Player) => game.state().flipped(),
+        };
+
+        if let Some(tie_break) = &self.tie_break {
+            state.set_tie_break(tie_break.clone());
+        }
+        state.set_skipped(self.skipped.clone());
+
+        Ok(state)
+    }
+}
+
+impl Engine for RuleBased {
+    fn choose_move(&mut self, game: &Game) -> Result<Position, GameError> {
+        let state = self.state_to_move(game)?;
+
+        match &mut self.observer {
+            Some(observer) => state.find_ai_move_observed(observer.as_mut()),
+            None => state.find_ai_move(),
+        }
+    }
+
+    fn choose_move_and_strategy(&mut self, game: &Game) -> Result<(Position, Option<Strategy>), GameError> {
+        self.explain_move(game).map(|explanation| (explanation.position, Some(explanation.strategy)))
+    }
+}
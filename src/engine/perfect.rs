@@ -0,0 +1,172 @@
+use crate::board::{Board, Position};
+use crate::engine::{Engine, Value};
+use crate::error::GameError;
+use crate::game::Game;
+use crate::tile::Tile;
+
+// Generated by build.rs. Each entry packs the optimal tiles into bits 0 to 8, the outcome
+// into bits 9 and 10 and the distance to the end of the game into bits 11 to 14.
+include!(concat!(env!("OUT_DIR"), "/perfect_play.rs"));
+
+/// The solved value of a 3x3 position and every tile which keeps that value
+#[derive(Debug, PartialEq, Eq, Copy, Clone)]
+pub struct Solution {
+    pub value: Value,
+    moves: u16,
+}
+
+impl Solution {
+    /// The tiles reaching the position's value, in reading order. Empty once the game is over.
+    pub fn moves(&self) -> Vec<Position> {
+        (0..9).filter(|cell| self.moves & 1 << cell != 0)
+            .map(|cell| (cell % 3, cell / 3))
+            .collect()
+    }
+
+    pub fn is_optimal(&self, (x, y): Position) -> bool {
+        x < 3 && y < 3 && self.moves & 1 << (y * 3 + x) != 0
+    }
+}
+
+/// The solution for `token` to move on `board`, read from a table built when the crate is
+/// compiled. Only 3x3 boards played to three in a row are covered, and only positions which
+/// can come up in a game.
+///
+/// ```
+/// use tictactoe::{Board, Tile};
+/// use tictactoe::engine::{perfect_play, Value};
+///
+/// let board : Board = "XX./OO./...".parse().unwrap();
+/// let solution = perfect_play(&board, Tile::X).unwrap();
+///
+/// assert_eq!(solution.value, Value::Win(1));
+/// assert_eq!(solution.moves(), vec![(2, 0)]);
+/// assert_eq!(perfect_play(&Board::new(), Tile::O).unwrap().value, Value::Draw);
+/// ```
+pub fn perfect_play(board: &Board, token: Tile) -> Option<Solution> {
+    if board.dimensions() != (3, 3, 3) {
+        return None
+    }
+
+    let code = (0..9).rev().fold(0, |code, cell| code * 3 + match board.tile(cell % 3, cell / 3) {
+        None => 0,
+        Some(Tile::X) => 1,
+        Some(Tile::O) => 2,
+    });
+    let entry = PERFECT_PLAY[code * 2 + (token == Tile::O) as usize];
+
+    let distance = (entry >> 11) as usize & 15;
+    let value = match (entry >> 9) & 3 {
+        1 => Value::Win(distance),
+        2 => Value::Draw,
+        3 => Value::Loss(distance),
+        _ => return None,
+    };
+
+    Some(Solution { value, moves: entry & 0x1ff })
+}
+
+/// Plays the first optimal tile from the precomputed table, without searching. Only 3x3
+/// games can be played.
+#[derive(Debug, Default, Copy, Clone)]
+pub struct Perfect;
+
+impl Engine for Perfect {
+    fn choose_move(&mut self, game: &Game) -> Result<Position, GameError> {
+        let token = game.token_to_move().ok_or(GameError::GameOver)?;
+        let (width, height, win_length) = game.board().dimensions();
+        if (width, height, win_length) != (3, 3, 3) {
+            return Err(GameError::InvalidSize { width, height, win_length })
+        }
+
+        // The table only holds positions which can come up in a game
+        let solution = perfect_play(game.board(), token).ok_or(GameError::UnreachablePosition)?;
+        solution.moves().first().copied().ok_or(GameError::NoMoveAvailable)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::conditions::Turn;
+
+    // Every position reachable from `game`, checked against a search and the rule-based engine
+    fn check_position(game: &super::Game, minimax: &mut super::super::Minimax, checked: &mut usize) {
+        use super::*;
+        use crate::engine::RuleBased;
+
+        let token = match game.token_to_move() {
+            Some(token) => token,
+            None => return,
+        };
+        *checked += 1;
+
+        let solution = perfect_play(game.board(), token).unwrap();
+        let (searched, value) = minimax.search(game.board(), token).unwrap();
+        assert_eq!(solution.value, value, "{:?} to move on {:?}", token, game.board());
+        assert!(solution.is_optimal(searched));
+
+        if game.turn() == Some(Turn::AI) {
+            let mut next = game.clone();
+            let position = next.ai_move_with(&mut RuleBased::new()).unwrap();
+            let reply = perfect_play(next.board(), token.other()).unwrap();
+            // The rule-based engine doesn't always take the quickest line, but it must reach the same outcome
+            assert_eq!(reply.value.parent().cmp(&Value::Draw), solution.value.cmp(&Value::Draw),
+                "{:?} played {:?} on {:?}", token, position, game.board());
+
+            check_position(&next, minimax, checked);
+        } else {
+            for (x, y) in game.board().empty_tiles() {
+                let mut next = game.clone();
+                next.player_move(x, y).unwrap();
+                check_position(&next, minimax, checked);
+            }
+        }
+    }
+
+    #[test]
+    fn table_matches_search() {
+        use super::*;
+        use crate::engine::Minimax;
+
+        let mut minimax = Minimax::new();
+        let mut checked = 0;
+        for &player_token in &[Tile::X, Tile::O] {
+            for &first_turn in &[Turn::AI, Turn::Player] {
+                check_position(&Game::with_options(player_token, first_turn), &mut minimax, &mut checked);
+            }
+        }
+        assert!(checked > 1000);
+    }
+
+    #[test]
+    fn lookups() {
+        use super::*;
+
+        // Finished and impossible positions
+        let won : Board = "XXX/OO./...".parse().unwrap();
+        assert_eq!(perfect_play(&won, Tile::O).map(|solution| solution.value), Some(Value::Loss(0)));
+        assert!(perfect_play(&won, Tile::O).unwrap().moves().is_empty());
+        assert_eq!(perfect_play(&won, Tile::X), None);
+        let mut impossible = Board::new();
+        for x in 0..3 {
+            impossible.make_move(x, 0, Tile::X).unwrap();
+        }
+        assert_eq!(perfect_play(&impossible, Tile::O), None);
+        assert_eq!(perfect_play(&Board::with_size(4, 4, 3).unwrap(), Tile::X), None);
+
+        // Every reply to a corner but the center loses
+        let corner : Board = "X../.../...".parse().unwrap();
+        assert_eq!(perfect_play(&corner, Tile::O).unwrap().moves(), vec![(1, 1)]);
+
+        let mut game = Game::new();
+        game.player_move(0, 0).unwrap();
+        assert_eq!(Perfect.choose_move(&game), Ok((1, 1)));
+        assert_eq!(Perfect.choose_move(&Game::with_board(Board::with_size(4, 4, 3).unwrap(), Tile::O, Turn::AI)),
+            Err(GameError::InvalidSize { width: 4, height: 4, win_length: 3 }));
+
+        // X can't be to move when it already has a tile more than O
+        let unreachable = Game::with_board("X../.../...".parse().unwrap(), Tile::O, Turn::AI);
+        assert_eq!(unreachable.token_to_move(), Some(Tile::X));
+        assert_eq!(Perfect.choose_move(&unreachable), Err(GameError::UnreachablePosition));
+    }
+}
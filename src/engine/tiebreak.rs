@@ -0,0 +1,86 @@
+use crate::board::{Board, Position};
+use crate::rng::Rng;
+
+#[cfg(feature = "serde")]
+use serde::{Deserialize, Serialize};
+
+/// How a strategy picks between tiles which are equally good
+#[derive(Debug, PartialEq, Eq, Clone, Default)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
+pub enum TieBreak {
+    /// The first tile row by row, so `(2, 0)` comes before `(0, 1)`
+    #[default]
+    LowestCoordinate,
+    /// A uniformly random tile. The choice only depends on `seed` and the board, so the
+    /// same seed always picks the same tile in the same position.
+    Random { seed: u64 },
+    /// The first tile in the list, falling back to the lowest coordinate when none of
+    /// them are candidates
+    Priority(Vec<Position>),
+}
+
+impl TieBreak {
+    /// Picks one of `candidates`, or `None` when there are none
+    pub fn choose(&self, board: &Board, candidates: &[Position]) -> Option<Position> {
+        let lowest = candidates.iter().min_by_key(|&&(x, y)| (y, x)).copied();
+
+        match self {
+            TieBreak::LowestCoordinate => lowest,
+            TieBreak::Random { seed } => {
+                if candidates.is_empty() {
+                    return None
+                }
+
+                // Sorted so the order candidates were found in can't change the choice
+                let mut sorted = candidates.to_vec();
+                sorted.sort_by_key(|&(x, y)| (y, x));
+
+                let mut rng = Rng::new(seed ^ board.fingerprint());
+                Some(sorted[rng.below(sorted.len())])
+            },
+            TieBreak::Priority(priority) => {
+                priority.iter().find(|tile| candidates.contains(tile)).copied().or(lowest)
+            },
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    #[test]
+    fn choices() {
+        use super::*;
+
+        let board = Board::new();
+        let corners = [(2, 2), (0, 2), (2, 0), (0, 0)];
+
+        assert_eq!(TieBreak::LowestCoordinate.choose(&board, &corners), Some((0, 0)));
+        assert_eq!(TieBreak::Priority(vec![(1, 1), (0, 2)]).choose(&board, &corners), Some((0, 2)));
+        assert_eq!(TieBreak::Priority(vec![(1, 1)]).choose(&board, &corners), Some((0, 0)));
+        assert_eq!(TieBreak::Random { seed: 7 }.choose(&board, &[]), None);
+    }
+
+    #[test]
+    fn random_is_reproducible() {
+        use super::*;
+
+        let board = Board::new();
+        let corners = [(0, 0), (2, 0), (0, 2), (2, 2)];
+        let reversed = [(2, 2), (0, 2), (2, 0), (0, 0)];
+
+        let mut seen = Vec::new();
+        for seed in 0..32 {
+            let random = TieBreak::Random { seed };
+            let choice = random.choose(&board, &corners);
+
+            assert_eq!(choice, random.choose(&board, &corners));
+            assert_eq!(choice, random.choose(&board, &reversed));
+
+            if !seen.contains(&choice) {
+                seen.push(choice);
+            }
+        }
+
+        assert_eq!(seen.len(), 4);
+    }
+}
@@ -0,0 +1,178 @@
+use crate::board::{Board, Position};
+use crate::tile::Tile;
+
+// Mixed into the key when O is to move, so the same tiles with a different side to move
+// are kept apart
+const O_TO_MOVE : u64 = 0x9d39_247e_3377_6d41;
+
+/// Entries kept by a table made with `TranspositionTable::default`
+pub const DEFAULT_CAPACITY : usize = 1 << 15;
+
+/// How a stored score relates to the true score of the position
+#[derive(Debug, PartialEq, Eq, Copy, Clone)]
+pub enum Bound {
+    /// The score is exact
+    Exact,
+    /// The search was cut off, so the true score is at least this high
+    Lower,
+    /// No move reached the window, so the true score is at most this high
+    Upper,
+}
+
+/// What a search found out about one position
+#[derive(Debug, PartialEq, Eq, Copy, Clone)]
+pub struct Entry {
+    pub key: u64,
+    /// How many moves deep the position was searched
+    pub depth: usize,
+    pub score: i32,
+    pub bound: Bound,
+    /// The best move found, tried first when the position is searched again
+    pub best: Option<Position>,
+}
+
+/// A fixed size cache of searched positions, keyed by zobrist hash, which a search engine
+/// can keep between moves or hand on to another engine.
+///
+/// Each key has a single slot. When two positions share a slot the one searched to the
+/// greater depth is kept, with ties going to the newer entry.
+#[derive(Debug, Clone)]
+pub struct TranspositionTable {
+    slots: Vec<Option<Entry>>,
+    len: usize,
+}
+
+impl Default for TranspositionTable {
+    fn default() -> Self {
+        TranspositionTable::new(DEFAULT_CAPACITY)
+    }
+}
+
+impl TranspositionTable {
+    /// A table holding at most `capacity` entries, where 0 stores nothing at all
+    pub fn new(capacity: usize) -> Self {
+        TranspositionTable {
+            slots: vec![None; capacity],
+            len: 0,
+        }
+    }
+
+    /// The key for `board` with `token` to move
+    pub fn key(board: &Board, token: Tile) -> u64 {
+        match token {
+            Tile::X => board.zobrist(),
+            Tile::O => board.zobrist() ^ O_TO_MOVE,
+        }
+    }
+
+    pub fn capacity(&self) -> usize {
+        self.slots.len()
+    }
+
+    /// Number of entries stored
+    pub fn len(&self) -> usize {
+        self.len
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.len == 0
+    }
+
+    /// The entry stored for `key`, if it hasn't been replaced
+    pub fn probe(&self, key: u64) -> Option<&Entry> {
+        self.slot(key)
+            .and_then(|index| self.slots[index].as_ref())
+            .filter(|entry| entry.key == key)
+    }
+
+    /// Stores `entry`, unless its slot holds a different position searched more deeply
+    pub fn store(&mut self, entry: Entry) {
+        let index = match self.slot(entry.key) {
+            Some(index) => index,
+            None => return,
+        };
+
+        match &self.slots[index] {
+            Some(existing) if existing.key != entry.key && existing.depth > entry.depth => (),
+            Some(_) => self.slots[index] = Some(entry),
+            None => {
+                self.slots[index] = Some(entry);
+                self.len += 1;
+            },
+        }
+    }
+
+    pub fn clear(&mut self) {
+        self.slots.iter_mut().for_each(|slot| *slot = None);
+        self.len = 0;
+    }
+
+    fn slot(&self, key: u64) -> Option<usize> {
+        if self.slots.is_empty() {
+            None
+        } else {
+            Some((key % self.slots.len() as u64) as usize)
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    #[test]
+    fn replacement() {
+        use super::*;
+
+        let entry = |key: u64, depth: usize| Entry { key, depth, score: 0, bound: Bound::Exact, best: None };
+        let mut table = TranspositionTable::new(4);
+
+        table.store(entry(1, 3));
+        assert_eq!(table.probe(1), Some(&entry(1, 3)));
+        assert_eq!(table.probe(5), None);
+
+        // Key 5 shares a slot with key 1, and only wins it by searching at least as deep
+        table.store(entry(5, 2));
+        assert_eq!(table.probe(1), Some(&entry(1, 3)));
+        table.store(entry(5, 3));
+        assert_eq!(table.probe(5), Some(&entry(5, 3)));
+        assert_eq!(table.probe(1), None);
+
+        // The same position is always updated
+        table.store(entry(5, 1));
+        assert_eq!(table.probe(5), Some(&entry(5, 1)));
+
+        table.store(entry(2, 0));
+        assert_eq!(table.len(), 2);
+        table.clear();
+        assert!(table.is_empty());
+
+        let mut disabled = TranspositionTable::new(0);
+        disabled.store(entry(1, 1));
+        assert_eq!(disabled.probe(1), None);
+    }
+
+    #[test]
+    fn keys_follow_the_board() {
+        use super::*;
+
+        let mut board = Board::new();
+        let empty = TranspositionTable::key(&board, Tile::X);
+        assert_ne!(empty, TranspositionTable::key(&board, Tile::O));
+
+        board.make_move(0, 0, Tile::X).unwrap();
+        board.make_move(1, 1, Tile::O).unwrap();
+        let mut other_order = Board::new();
+        other_order.make_move(1, 1, Tile::O).unwrap();
+        other_order.make_move(0, 0, Tile::X).unwrap();
+        assert_eq!(board.zobrist(), other_order.zobrist());
+
+        board.undo_move(1, 1);
+        board.undo_move(0, 0);
+        assert_eq!(TranspositionTable::key(&board, Tile::X), empty);
+
+        // Boards of different sizes use different keys
+        let mut larger = Board::with_size(4, 4, 3).unwrap();
+        larger.make_move(0, 0, Tile::X).unwrap();
+        board.make_move(0, 0, Tile::X).unwrap();
+        assert_ne!(larger.zobrist(), board.zobrist());
+    }
+}
@@ -0,0 +1,42 @@
+use crate::tile::Tile;
+
+use std::error::Error;
+use std::fmt;
+
+/// Reasons a board can't be set up, or a move can't be made or found
+#[derive(Debug, PartialEq, Copy, Clone)]
+pub enum GameError {
+    /// The board dimensions can't be played on
+    InvalidSize { width: usize, height: usize, win_length: usize },
+    /// The coordinates are not on the board
+    OutOfBounds { x: usize, y: usize },
+    /// The tile has already been claimed
+    Occupied { x: usize, y: usize, by: Tile },
+    /// The game has already finished
+    GameOver,
+    /// The other side is expected to move
+    NotYourTurn,
+    /// No strategy produced a move
+    NoMoveAvailable,
+    /// A saved game's board, turn and moves don't agree with each other
+    InconsistentSave,
+    /// The board and side to move couldn't come about by taking turns from an empty board
+    UnreachablePosition,
+}
+
+impl fmt::Display for GameError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            GameError::InvalidSize { width, height, win_length } => write!(f, "Can't play {} in a row on a {}x{} board", win_length, width, height),
+            GameError::OutOfBounds { x, y } => write!(f, "Coordinates not found for Tile : ({}, {})", x, y),
+            GameError::Occupied { x, y, by } => write!(f, "Tile ({}, {}) is already claimed by {:?}", x, y, by),
+            GameError::GameOver => write!(f, "Game is over"),
+            GameError::NotYourTurn => write!(f, "It is not your turn"),
+            GameError::NoMoveAvailable => write!(f, "No valid move found"),
+            GameError::InconsistentSave => write!(f, "Saved game doesn't match its board"),
+            GameError::UnreachablePosition => write!(f, "Position can't be reached by taking turns"),
+        }
+    }
+}
+
+impl Error for GameError {}
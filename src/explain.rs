@@ -0,0 +1,80 @@
+use crate::board::Position;
+use crate::conditions::{Direction, Line};
+use crate::strategy::Strategy;
+
+use std::fmt;
+
+/// Why the rule-based engine chose a tile
+#[derive(Debug, PartialEq, Eq, Clone)]
+pub struct Explanation {
+    pub position: Position,
+    pub strategy: Strategy,
+    pub evidence: Evidence,
+}
+
+/// What on the board made a strategy pick its tile
+#[derive(Debug, PartialEq, Eq, Clone)]
+pub enum Evidence {
+    /// The move finishes this line for the mover
+    Completes(Line),
+    /// The move stops the opponent finishing this line
+    Blocks(Line),
+    /// The move leaves each of these lines one tile from finished, more than the
+    /// opponent can block in one move
+    Fork(Vec<Line>),
+    /// The move deals with every tile where the opponent could have forked. `forcing`
+    /// is the line the move threatens, when it makes the opponent defend instead.
+    BlockFork { forks: Vec<Position>, forcing: Option<Line> },
+    /// The move answers the opponent's corner with the one across from it
+    OppositeCorner { corner: Position },
+    /// The strategy only looks at where the tile is
+    Placement,
+}
+
+impl fmt::Display for Explanation {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        let (x, y) = self.position;
+
+        match &self.evidence {
+            Evidence::Completes(line) => write!(f, "({}, {}) completes the {}", x, y, describe(line)),
+            Evidence::Blocks(line) => write!(f, "({}, {}) blocks the opponent's {}", x, y, describe(line)),
+            Evidence::Fork(lines) => {
+                write!(f, "({}, {}) threatens", x, y)?;
+                for (index, line) in lines.iter().enumerate() {
+                    let separator = if index == 0 { " the" } else { " and the" };
+                    write!(f, "{} {}", separator, describe(line))?;
+                }
+                Ok(())
+            },
+            Evidence::BlockFork { forcing: Some(line), .. } => {
+                write!(f, "({}, {}) forces a block on the {} so the opponent can't fork", x, y, describe(line))
+            },
+            Evidence::BlockFork { forcing: None, .. } => write!(f, "({}, {}) takes away the opponent's fork", x, y),
+            Evidence::OppositeCorner { corner } => {
+                write!(f, "({}, {}) takes the corner across from ({}, {})", x, y, corner.0, corner.1)
+            },
+            // Only the placement stages have nothing more to show, but the others fall back to
+            // naming their strategy when the board doesn't back them up
+            Evidence::Placement => match self.strategy {
+                Strategy::Win => write!(f, "({}, {}) wins", x, y),
+                Strategy::Block => write!(f, "({}, {}) blocks the opponent", x, y),
+                Strategy::Fork => write!(f, "({}, {}) forks", x, y),
+                Strategy::BlockFork => write!(f, "({}, {}) blocks the opponent's fork", x, y),
+                Strategy::Center => write!(f, "({}, {}) takes the center", x, y),
+                Strategy::OppositeCorner => write!(f, "({}, {}) takes the opposite corner", x, y),
+                Strategy::EmptyCorner => write!(f, "({}, {}) takes an empty corner", x, y),
+                Strategy::EmptySide => write!(f, "({}, {}) takes an empty side", x, y),
+            },
+        }
+    }
+}
+
+fn describe(line: &Line) -> String {
+    let name = match line.direction {
+        Direction::Row => "row",
+        Direction::Column => "column",
+        Direction::MatchDiagonal | Direction::UnmatchDiagonal => "diagonal",
+    };
+
+    format!("{} from ({}, {})", name, line.start.0, line.start.1)
+}
@@ -1,428 +1,778 @@
-use crate::board::Board;
+use crate::board::{Board, Position};
 use crate::strategy::Strategy;
 use crate::tile::Tile;
-use crate::conditions::{Direction, CheckType, EmptyTile, Winner};
-
-use std::collections::HashMap;
-
-static BOARD_SIZE : usize = 3;
-
-struct GameState {
+use crate::conditions::{Line, GameStatus, Turn, Winner};
+#[cfg(test)]
+use crate::conditions::CheckType;
+use crate::engine::{Engine, Evaluation, Minimax, RuleBased, TieBreak};
+use crate::error::GameError;
+use crate::explain::{Evidence, Explanation};
+use crate::history::Move;
+use crate::observer::{Event, Observer};
+
+use std::fmt;
+
+#[cfg(feature = "serde")]
+use serde::{Deserialize, Serialize};
+#[cfg(feature = "serde")]
+use std::convert::TryFrom;
+
+#[derive(Clone)]
+pub(crate) struct GameState {
     board : Board,
     ai_token : Tile,
     player_token: Tile,
     last_move: Option<(usize, usize)>,
     filled_tiles : usize,
+    turn: Turn,
+    status: GameStatus,
+    tie_break: TieBreak,
+    skipped: Vec<Strategy>,
+    history: Vec<Move>,
+    // Undone moves, the most recently undone last
+    undone: Vec<Move>,
 }
 
 impl GameState {
     pub fn new() -> Self {
-        GameState {
-            board: Board::new(),
+        GameState::with_board(Board::new())
+    }
+
+    pub fn with_board(board: Board) -> Self {
+        let mut state = GameState {
+            filled_tiles: board.filled_tiles(),
+            board,
             ai_token: Tile::X,
             player_token: Tile::O,
             last_move: None,
-            filled_tiles: 0,
-        }
+            turn: Turn::Player,
+            status: GameStatus::InProgress,
+            tie_break: TieBreak::default(),
+            skipped: Vec::new(),
+            history: Vec::new(),
+            undone: Vec::new(),
+        };
+        state.status = state.check_status();
+
+        state
+    }
+
+    pub fn set_first_turn(&mut self, turn: Turn) {
+        self.turn = turn;
+    }
+
+    pub fn set_tie_break(&mut self, tie_break: TieBreak) {
+        self.tie_break = tie_break;
+    }
+
+    /// Stages the AI never tries, as if it didn't know about them
+    pub fn set_skipped(&mut self, skipped: Vec<Strategy>) {
+        self.skipped = skipped;
     }
 
     pub fn set_player_token(&mut self, tile: Tile) {
         self.player_token = tile;
+        self.ai_token = tile.other();
+        self.status = self.check_status();
+    }
 
-        match tile {
-            Tile::X => self.ai_token = Tile::O,
-            Tile::O => self.ai_token = Tile::X,
-        }
+    /// The same position seen from the other side of the board, so the AI's strategy
+    /// can be used to choose the player's move
+    pub fn flipped(&self) -> GameState {
+        let mut flipped = self.clone();
+        flipped.ai_token = self.player_token;
+        flipped.player_token = self.ai_token;
+        flipped.turn = self.turn.other();
+        flipped.status = flipped.check_status();
+
+        flipped
     }
 
-    pub fn find_ai_move(&self) -> Result<(usize, usize), String> {
+    pub fn find_ai_move(&self) -> Result<(usize, usize), GameError> {
+        self.find_ai_move_observed(&mut |_: &Event| ())
+    }
+
+    pub fn find_ai_move_observed(&self, observer: &mut dyn Observer) -> Result<(usize, usize), GameError> {
+        self.find_ai_strategy(observer).map(|(valid_move, _)| valid_move)
+    }
+
+    /// The move the AI would make, why it would make it, and what on the board backs that up
+    pub fn explain_ai_move(&self, observer: &mut dyn Observer) -> Result<Explanation, GameError> {
+        let (position, strategy) = self.find_ai_strategy(observer)?;
+
+        Ok(Explanation {
+            position,
+            strategy,
+            evidence: self.find_evidence(strategy, position),
+        })
+    }
+
+    fn find_ai_strategy(&self, observer: &mut dyn Observer) -> Result<((usize, usize), Strategy), GameError> {
         // Game over
-        if self.filled_tiles == BOARD_SIZE * BOARD_SIZE {
-            return Err(String::from("Game is over"))
-        }
-
-        let mut state : Strategy = Strategy::Win;
-        loop {
-            match state {
-                Strategy::Win => {
-                    println!("Checking strategy win...");
-                    if let Some(valid_move) = self.check_win_block_condition(CheckType::Win) {
-                        return Ok(valid_move);
-                    } else {
-                        state = Strategy::Block;
-                    }
-                },
-                Strategy::Block => {
-                    println!("Checking strategy block...");
-                    if let Some(valid_move) = self.check_win_block_condition(CheckType::Block) {
-                        return Ok(valid_move);
-                    } else {
-                        state = Strategy::Fork;
-                    }
-                },
-                Strategy::Fork => {
-                    println!("Checking strategy fork...");
-                },
-                Strategy::BlockFork => {
-                    println!("Checking strategy block fork...");
-                },
-                Strategy::Center => {
-                    println!("Checking strategy center...");
-                    if let Some(valid_move) = self.check_center() {
-                        return Ok(valid_move);
-                    } else {
-                        state = Strategy::OppositeCorner;
-                    }
-                },
-                Strategy::OppositeCorner => {
-                    println!("Checking strategy opposite corner...");
-                    if let Some(valid_move) = self.check_corner() {
-                        return Ok(valid_move);
-                    } else {
-                        state = Strategy::EmptyCorner;
-                    }
-                },
-                Strategy::EmptyCorner => {
-                    println!("Checking strategy empty corner...");
-                    if let Some(valid_move) = self.find_empty(EmptyTile::Corner) {
-                        return Ok(valid_move);
-                    } else {
-                        state = Strategy::EmptySide;
-                    }
-                },
-                Strategy::EmptySide => {
-                    println!("Checking strategy empty side...");
-                    if let Some(valid_move) = self.find_empty(EmptyTile::Side) {
-                        return Ok(valid_move);
-                    } else {
-                        return Err(String::from("No valid move found"));
-                    }
+        if self.status != GameStatus::InProgress {
+            return Err(GameError::GameOver)
+        }
+
+        for &state in Strategy::ALL.iter() {
+            if !self.skipped.contains(&state) {
+                if let Some(valid_move) = self.run_stage(state, observer) {
+                    return Ok((valid_move, state))
+                }
+            }
+        }
+
+        // Skipped fallbacks still run when nothing else found a move, so there is always a
+        // move while any tile is empty
+        for &state in [Strategy::EmptyCorner, Strategy::EmptySide].iter() {
+            if self.skipped.contains(&state) {
+                if let Some(valid_move) = self.run_stage(state, observer) {
+                    return Ok((valid_move, state))
                 }
             }
         }
+
+        Err(GameError::NoMoveAvailable)
     }
 
-    fn check_win_block_condition(&self, check: CheckType) -> Option<(usize, usize)> {
-        let mut board_state : HashMap<Direction, (usize, usize, usize)> = HashMap::new();
+    fn run_stage(&self, state: Strategy, observer: &mut dyn Observer) -> Option<(usize, usize)> {
+        observer.on_event(&Event::StageEntered(state));
 
-        let check_token = {
-            match check {
-                CheckType::Win => self.ai_token,
-                CheckType::Block => self.player_token,
-            }
+        let candidates = self.strategy_candidates(state);
+        for &position in candidates.iter() {
+            observer.on_event(&Event::CandidateFound { strategy: state, position });
+        }
+
+        let valid_move = self.tie_break.choose(&self.board, &candidates)?;
+        observer.on_event(&Event::MoveChosen { strategy: state, position: valid_move });
+        Some(valid_move)
+    }
+
+    fn find_evidence(&self, strategy: Strategy, (x, y): (usize, usize)) -> Evidence {
+        let win_length = self.board.win_length();
+        // Lines through the tile where `token` needs just one more tile
+        let open_lines = |board: &Board, token: Tile| -> Vec<Line> {
+            board.lines_through(x, y).into_iter()
+                .filter(|line| board.line_counts(line, token) == (win_length - 1, 1))
+                .collect()
         };
 
-        self.board.iter().for_each(|tile_entry| {
-            if let Some(fill) = tile_entry.1 {
-                let addin = {
-                    if *fill == check_token {
-                        1
-                    } else {
-                        // This is just so that opponents tokens are recognized
-                        3
-                    }
-                };
-
-                let (x, y) = tile_entry.0;
-                let row = Direction::Row(*y);
-                let col = Direction::Column(*x);
-
-                board_state.entry(row).and_modify(|entry| {
-                    *entry = (entry.0 + x, entry.1 + y, entry.2 + addin)
-                }).or_insert((*x, *y, addin));
-
-                board_state.entry(col).and_modify(|entry| {
-                    *entry = (entry.0 + x, entry.1 + y, entry.2 + addin);
-                }).or_insert((*x, *y, addin));
-
-                // Diagonal Check
-                if (x + y) % 2 == 0 {
-                    // In the center case, we make changes to both the matching diagonal
-                    // and the unmatching diagonal
-                    // let match_diag = ;
-                    // let unmatch_diag = ;
-                    if *x == *y && *x == 1 {
-                        board_state.entry(Direction::MatchDiagonal).and_modify(|entry| {
-                            *entry = (entry.0 + x, entry.1 + y, entry.2 + addin);
-                        }).or_insert((*x, *y, addin));
-
-                        board_state.entry(Direction::UnmatchDiagonal).and_modify(|entry| {
-                            *entry = (entry.0 + x, entry.1 + y, entry.2 + addin);
-                        }).or_insert((*x, *y, addin));
-                    } 
-                    // In the matching diagonal case, we just add to the matching diagonal
-                    else if *x == *y && *x != addin {
-                        board_state.entry(Direction::MatchDiagonal).and_modify(|entry| {
-                            *entry = (entry.0 + x, entry.1 + y, entry.2 + addin);
-                        }).or_insert((*x, *y, addin));
-                    } 
-                    // In the unmatching diagonal case, we just add to the unmatching diagonal
-                    else {
-                        board_state.entry(Direction::UnmatchDiagonal).and_modify(|entry| {
-                            *entry = (entry.0 + x, entry.1 + y, entry.2 + addin);
-                        }).or_insert((*x, *y, addin));
-                    }
-                } 
-            }            
-        });
-
-        let wins : Vec<_> = board_state.iter().filter(|&direction| {
-            (*direction.1).2 == BOARD_SIZE - 1
-        }).collect();
+        let mut after = self.board.clone();
+        let _ = after.make_move(x, y, self.ai_token);
+
+        match strategy {
+            Strategy::Win => open_lines(&self.board, self.ai_token).first().copied()
+                .map_or(Evidence::Placement, Evidence::Completes),
+            Strategy::Block => open_lines(&self.board, self.player_token).first().copied()
+                .map_or(Evidence::Placement, Evidence::Blocks),
+            Strategy::Fork => Evidence::Fork(open_lines(&after, self.ai_token)),
+            Strategy::BlockFork => {
+                // Only moves from the forcing tiers threaten a line, a single fork is just taken
+                let tiers = self.block_fork_tiers();
+                let forced = tiers.len() > 1 && tiers.iter().any(|tier| tier.contains(&(x, y)));
+
+                Evidence::BlockFork {
+                    forks: Self::find_forks(&self.board, self.player_token),
+                    forcing: if forced { open_lines(&after, self.ai_token).first().copied() } else { None },
+                }
+            },
+            Strategy::OppositeCorner => match self.last_move {
+                Some(corner) => Evidence::OppositeCorner { corner },
+                None => Evidence::Placement,
+            },
+            Strategy::Center | Strategy::EmptyCorner | Strategy::EmptySide => Evidence::Placement,
+        }
+    }
+
+    #[cfg(test)]
+    fn check_win_block_condition(&self, check: CheckType) -> Option<(usize, usize)> {
+        let strategy = match check {
+            CheckType::Win => Strategy::Win,
+            CheckType::Block => Strategy::Block,
+        };
 
-        Some(((BOARD_SIZE - (*wins[0].1).0) % BOARD_SIZE, (BOARD_SIZE - (*wins[0].1).1) % BOARD_SIZE))
+        self.tie_break.choose(&self.board, &self.strategy_candidates(strategy))
     }
 
+    #[cfg(test)]
     fn check_fork_block_condition(&self, check: CheckType) -> Option<(usize, usize)> {
+        let check_token = self.check_token(&check);
 
-        let turn = self.filled_tiles / 2;
-        // We can't possibly have a fork before turn 2
-        if turn < 2 {
-            return None
+        self.tie_break.choose(&self.board, &Self::find_forks(&self.board, check_token))
+    }
+
+    /// Every tile `strategy` would be happy to play, which the tie break picks between
+    fn strategy_candidates(&self, strategy: Strategy) -> Vec<(usize, usize)> {
+        match strategy {
+            Strategy::Win => self.board.threats(self.ai_token),
+            Strategy::Block => self.board.threats(self.player_token),
+            Strategy::Fork => Self::find_forks(&self.board, self.ai_token),
+            Strategy::BlockFork => self.block_fork_tiers().into_iter()
+                .find(|tier| !tier.is_empty())
+                .unwrap_or_default(),
+            Strategy::Center => self.check_center().into_iter().collect(),
+            Strategy::OppositeCorner => self.check_corner().into_iter().collect(),
+            Strategy::EmptyCorner | Strategy::EmptySide => {
+                let want_corner = strategy == Strategy::EmptyCorner;
+
+                self.board.empty_tiles().into_iter()
+                    .filter(|&(x, y)| self.is_corner(x, y) == want_corner)
+                    .collect()
+            },
         }
+    }
 
-        let mut fork_move = None;
-        
-        self.board.iter().for_each(|tile_outer| {
-            let mut fork_board = self.board.clone();
-            if *tile_outer.1 == None && fork_move == None {
-                if check == CheckType::Win {
-                    println!("Move {} : {}", (*tile_outer.0).0, (*tile_outer.0).0);
-                    fork_board.make_move((*tile_outer.0).0, (*tile_outer.0).1, self.ai_token);
-                } else {
-                    fork_board.make_move((*tile_outer.0).0, (*tile_outer.0).1, self.player_token);
-                }
+    /// Every tile which stops the opponent forking, best first. The engine only picks between
+    /// the tiles of the first tier which isn't empty.
+    fn block_fork_tiers(&self) -> Vec<Vec<(usize, usize)>> {
+        let forks = Self::find_forks(&self.board, self.player_token);
 
-                let mut board_state : HashMap<Direction, (usize, usize, usize)> = HashMap::new();
-
-                let check_token = {
-                    match check {
-                        CheckType::Win => self.ai_token,
-                        CheckType::Block => self.player_token,
-                    }
-                };
-
-                fork_board.iter().for_each(|tile_entry| {
-                    if let Some(fill) = tile_entry.1 {
-                        let addin = {
-                            if *fill == check_token {
-                                1
-                            } else {
-                                // This is just so that opponents tokens are recognized
-                                3
-                            }
-                        };
-
-                        let (x, y) = tile_entry.0;
-                        let row = Direction::Row(*y);
-                        let col = Direction::Column(*x);
-
-                        board_state.entry(row).and_modify(|entry| {
-                            *entry = (entry.0 + x, entry.1 + y, entry.2 + addin)
-                        }).or_insert((*x, *y, addin));
-
-                        board_state.entry(col).and_modify(|entry| {
-                            *entry = (entry.0 + x, entry.1 + y, entry.2 + addin);
-                        }).or_insert((*x, *y, addin));
-
-                        // Diagonal Check
-                        if (x + y) % 2 == 0 {
-                            // In the center case, we make changes to both the matching diagonal
-                            // and the unmatching diagonal
-                            // let match_diag = ;
-                            // let unmatch_diag = ;
-                            if *x == *y && *x == 1 {
-                                board_state.entry(Direction::MatchDiagonal).and_modify(|entry| {
-                                    *entry = (entry.0 + x, entry.1 + y, entry.2 + addin);
-                                }).or_insert((*x, *y, addin));
-
-                                board_state.entry(Direction::UnmatchDiagonal).and_modify(|entry| {
-                                    *entry = (entry.0 + x, entry.1 + y, entry.2 + addin);
-                                }).or_insert((*x, *y, addin));
-                            } 
-                            // In the matching diagonal case, we just add to the matching diagonal
-                            else if *x == *y && *x != 1 {
-                                board_state.entry(Direction::MatchDiagonal).and_modify(|entry| {
-                                    *entry = (entry.0 + x, entry.1 + y, entry.2 + addin);
-                                }).or_insert((*x, *y, addin));
-                            } 
-                            // In the unmatching diagonal case, we just add to the unmatching diagonal
-                            else {
-                                board_state.entry(Direction::UnmatchDiagonal).and_modify(|entry| {
-                                    *entry = (entry.0 + x, entry.1 + y, entry.2 + addin);
-                                }).or_insert((*x, *y, addin));
-                            }
-                        }
-                    }            
-                });
-
-                let wins : Vec<_> = board_state.iter().filter(|&direction| {
-                    (*direction.1).2 == BOARD_SIZE - 1
-                }).collect();
-
-                if wins.len() >= 2 {
-                    fork_move = Some(((*tile_outer.0).0, (*tile_outer.0).1))
-                }
+        // A single fork can simply be taken away from the opponent
+        if forks.len() < 2 {
+            return vec![forks]
+        }
+
+        // Multiple forks can't all be blocked, so force the opponent to defend instead,
+        // preferring a forcing move that also sits on one of their fork tiles
+        let forcing = self.find_forcing_moves();
+        if forcing.is_empty() {
+            return vec![forks]
+        }
+
+        let (blocking, others) = forcing.into_iter().partition(|tile| forks.contains(tile));
+        vec![blocking, others]
+    }
+
+    /// Moves which create a two in a row for the AI, where the opponents forced reply
+    /// does not leave them with a fork of their own.
+    fn find_forcing_moves(&self) -> Vec<(usize, usize)> {
+        self.board.empty_tiles().into_iter().filter(|&(x, y)| {
+            let mut forcing_board = self.board.clone();
+            let _ = forcing_board.make_move(x, y, self.ai_token);
+
+            let threats = forcing_board.threats(self.ai_token);
+            if threats.len() != 1 {
+                return false
             }
-        });
 
-        fork_move
+            let (reply_x, reply_y) = threats[0];
+            let _ = forcing_board.make_move(reply_x, reply_y, self.player_token);
+
+            forcing_board.threats(self.player_token).len() < 2
+        }).collect()
+    }
+
+    #[cfg(test)]
+    fn check_token(&self, check: &CheckType) -> Tile {
+        match check {
+            CheckType::Win => self.ai_token,
+            CheckType::Block => self.player_token,
+        }
+    }
+
+    /// Every empty tile which would give `token` two ways to win at once
+    fn find_forks(board: &Board, token: Tile) -> Vec<(usize, usize)> {
+        board.empty_tiles().into_iter().filter(|&(x, y)| {
+            let mut fork_board = board.clone();
+            let _ = fork_board.make_move(x, y, token);
+
+            fork_board.threats(token).len() >= 2
+        }).collect()
     }
 
     fn check_center(&self) -> Option<(usize, usize)> {
-        let center = (BOARD_SIZE - 1) / 2;
-        if let Some(_) = self.board.get(&(center, center))? {
-            println!("{:?}", self.board.get(&(center, center)));
+        let center = ((self.board.width() - 1) / 2, (self.board.height() - 1) / 2);
+        if self.board.tile(center.0, center.1).is_some() {
             None
         } else {
-            Some((center, center))
+            Some(center)
         }
     }
 
+    fn is_corner(&self, x: usize, y: usize) -> bool {
+        (x == 0 || x == self.board.width() - 1) && (y == 0 || y == self.board.height() - 1)
+    }
+
     fn check_corner(&self) -> Option<(usize, usize)> {
-        let corner = BOARD_SIZE - 1;
-
-        if let Some(last_move) = self.last_move {
-            if last_move.0 == last_move.1 && last_move.0 == 0 {
-                if let Some(_) = self.board.get(&(corner, corner))? {
-                    None
-                } else {
-                    Some((corner, corner))
-                }
-            } 
-            else if last_move.0 == last_move.1 && last_move.0 != 0 {
-                if let Some(_) = self.board.get(&(0, 0))? {
-                    None
-                } else {
-                    Some((corner, corner))
-                }
-            }
-            else {
-                if let Some(_) = self.board.get(&(last_move.1, last_move.0))? {
-                    None
-                } else {
-                    Some((last_move.1, last_move.0))
+        let (x, y) = self.last_move?;
+        // Only a corner has an opposite corner
+        if !self.is_corner(x, y) {
+            return None
+        }
+
+        let opposite = (self.board.width() - 1 - x, self.board.height() - 1 - y);
+        if !self.board.is_legal_move(opposite.0, opposite.1) {
+            None
+        } else {
+            Some(opposite)
+        }
+    }
+
+    /// Every empty tile, ranked by the first strategy which would play it and then by the
+    /// tie break, each explained
+    pub fn suggest_moves(&self) -> Vec<Explanation> {
+        let mut suggestions : Vec<Explanation> = Vec::new();
+
+        if self.status != GameStatus::InProgress {
+            return suggestions
+        }
+
+        for &strategy in Strategy::ALL.iter() {
+            // Every forcing move blocks a double fork, not just the ones the engine prefers
+            let tiers = match strategy {
+                Strategy::BlockFork => self.block_fork_tiers(),
+                _ => vec![self.strategy_candidates(strategy)],
+            };
+
+            for mut candidates in tiers {
+                candidates.retain(|candidate| suggestions.iter().all(|suggestion| suggestion.position != *candidate));
+
+                while let Some(position) = self.tie_break.choose(&self.board, &candidates) {
+                    candidates.retain(|&candidate| candidate != position);
+                    suggestions.push(Explanation {
+                        position,
+                        strategy,
+                        evidence: self.find_evidence(strategy, position),
+                    });
                 }
             }
+        }
+
+        suggestions
+    }
+
+    fn check_status(&self) -> GameStatus {
+        if let Some((token, line)) = self.board.winner() {
+            let by = if token == self.ai_token { Winner::AI } else { Winner::Player };
+            GameStatus::Won { by, line }
+        } else if self.board.is_full() {
+            GameStatus::Draw
         } else {
-            None
+            GameStatus::InProgress
         }
     }
 
-    fn find_empty(&self, empty: EmptyTile) -> Option<(usize, usize)> {
+    #[cfg(test)]
+    fn make_ai_move(&mut self, x: usize, y: usize) -> Result<(), GameError> {
+        self.make_move(Turn::AI, x, y, None)
+    }
 
-        let tile_filter = {
-            match empty {
-                EmptyTile::Corner => 0,
-                EmptyTile::Side => 1,
-            }
+    fn make_player_move(&mut self, x: usize, y: usize) -> Result<(), GameError> {
+        self.make_move(Turn::Player, x, y, None)
+    }
+
+    fn make_move(&mut self, turn: Turn, x: usize, y: usize, strategy: Option<Strategy>) -> Result<(), GameError> {
+        self.play(turn, x, y, strategy)?;
+        self.undone.clear();
+
+        Ok(())
+    }
+
+    fn play(&mut self, turn: Turn, x: usize, y: usize, strategy: Option<Strategy>) -> Result<(), GameError> {
+        if self.status != GameStatus::InProgress {
+            return Err(GameError::GameOver)
+        }
+
+        if self.turn != turn {
+            return Err(GameError::NotYourTurn)
+        }
+
+        let token = match turn {
+            Turn::AI => self.ai_token,
+            Turn::Player => self.player_token,
         };
+        self.board.make_move(x, y, token)?;
 
-        let mut found_move = None;
+        self.history.push(Move { turn, token, position: (x, y), strategy });
+        self.last_move = Some((x, y));
+        self.filled_tiles += 1;
+        self.turn = turn.other();
+        self.status = self.check_status();
 
-        self.board.iter().for_each(|tile_entry| {
-            if *tile_entry.1 == None {
-                if ((*tile_entry.0).0 + (*tile_entry.0).1) % 2 == tile_filter {
-                    found_move = Some(((*tile_entry.0).0, (*tile_entry.0).1));
-                }
+        Ok(())
+    }
+
+    /// Takes back the last move, leaving it to be redone
+    pub fn undo(&mut self) -> Option<Move> {
+        let undone = self.history.pop()?;
+        let (x, y) = undone.position;
+        self.board.undo_move(x, y);
+
+        self.last_move = self.history.last().map(|last| last.position);
+        self.filled_tiles -= 1;
+        self.turn = undone.turn;
+        self.status = self.check_status();
+
+        self.undone.push(undone);
+        Some(undone)
+    }
+
+    /// Plays the last move which was undone again, if nothing has been played since. A move
+    /// which can't be played any more is kept for later and `None` is returned.
+    pub fn redo(&mut self) -> Option<Move> {
+        let redone = *self.undone.last()?;
+        let (x, y) = redone.position;
+
+        self.play(redone.turn, x, y, redone.strategy).ok()?;
+        self.undone.pop();
+        Some(redone)
+    }
+}
+
+/// A game of tic tac toe between a human player and the engine.
+///
+/// ```
+/// use tictactoe::{Game, Tile, Turn};
+///
+/// let mut game = Game::with_options(Tile::X, Turn::Player);
+/// game.player_move(0, 0).unwrap();
+///
+/// assert_eq!(game.turn(), Some(Turn::AI));
+/// let (x, y) = game.ai_move().unwrap();
+/// assert_eq!(game.board().tile(x, y), Some(Tile::O));
+/// ```
+#[derive(Clone)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize), serde(try_from = "GameData", into = "GameData"))]
+pub struct Game {
+    state: GameState,
+}
+
+/// The serialized form of a game. Everything which can be worked out from the board and
+/// history is left out, apart from the status which is kept for readers.
+#[cfg(feature = "serde")]
+#[derive(Serialize, Deserialize)]
+struct GameData {
+    board: Board,
+    player_token: Tile,
+    turn: Turn,
+    status: GameStatus,
+    history: Vec<Move>,
+    undone: Vec<Move>,
+    tie_break: TieBreak,
+    skipped: Vec<Strategy>,
+}
+
+#[cfg(feature = "serde")]
+impl From<Game> for GameData {
+    fn from(game: Game) -> GameData {
+        let state = game.state;
+
+        GameData {
+            board: state.board,
+            player_token: state.player_token,
+            turn: state.turn,
+            status: state.status,
+            history: state.history,
+            undone: state.undone,
+            tie_break: state.tie_break,
+            skipped: state.skipped,
+        }
+    }
+}
+
+#[cfg(feature = "serde")]
+impl TryFrom<GameData> for Game {
+    type Error = GameError;
+
+    fn try_from(data: GameData) -> Result<Game, GameError> {
+        // The board before the recorded moves, so they can be played again to check them
+        let mut start = data.board.clone();
+        for played in data.history.iter().rev() {
+            let (x, y) = played.position;
+            if start.undo_move(x, y) != Some(played.token) {
+                return Err(GameError::InconsistentSave)
             }
-        });
+        }
+
+        let mut state = GameState::with_board(start);
+        state.set_player_token(data.player_token);
+        state.set_first_turn(data.history.first().map_or(data.turn, |first| first.turn));
+        state.set_tie_break(data.tie_break);
+        state.set_skipped(data.skipped);
+
+        // Taking turns from the start has to lead to the same board, turn and status
+        for played in data.history.iter() {
+            replay(&mut state, played)?;
+        }
+        if state.board != data.board || state.turn != data.turn || state.status != data.status {
+            return Err(GameError::InconsistentSave)
+        }
+
+        // And every undone move has to be playable again, in the order redo plays them
+        let mut redone = state.clone();
+        for played in data.undone.iter().rev() {
+            replay(&mut redone, played)?;
+        }
+        state.undone = data.undone;
 
-        found_move
+        Ok(Game {
+            state,
+        })
     }
+}
 
-    fn is_game_won(&self) -> Option<Winner> {
-        let turn = self.filled_tiles / 2;
-        // We can't possibly have a win before turn 3
-        if turn < 3 {
-            return None
+#[cfg(feature = "serde")]
+fn replay(state: &mut GameState, played: &Move) -> Result<(), GameError> {
+    let (x, y) = played.position;
+    state.play(played.turn, x, y, played.strategy).map_err(|_| GameError::InconsistentSave)?;
+
+    match state.history.last() {
+        Some(last) if last.token == played.token => Ok(()),
+        _ => Err(GameError::InconsistentSave),
+    }
+}
+
+impl fmt::Debug for Game {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(f, "Game({})", self)
+    }
+}
+
+impl Default for Game {
+    fn default() -> Self {
+        Game::new()
+    }
+}
+
+impl Game {
+    /// Starts a game where the player uses `Tile::O` and moves first
+    pub fn new() -> Game {
+        Game::with_options(Tile::O, Turn::Player)
+    }
+
+    /// Starts a game with the player using `player_token`, and `first_turn` deciding who moves first
+    pub fn with_options(player_token: Tile, first_turn: Turn) -> Game {
+        Game::from_state(GameState::new(), player_token, first_turn)
+    }
+
+    /// Starts a game on `board`, which may be any size and may already hold tokens,
+    /// with `turn` deciding who moves next.
+    ///
+    /// ```
+    /// use tictactoe::{Board, Game, Tile, Turn};
+    ///
+    /// let board = Board::with_size(15, 15, 5).unwrap();
+    /// let mut game = Game::with_board(board, Tile::X, Turn::AI);
+    ///
+    /// assert_eq!(game.ai_move(), Ok((7, 7)));
+    /// ```
+    pub fn with_board(board: Board, player_token: Tile, turn: Turn) -> Game {
+        Game::from_state(GameState::with_board(board), player_token, turn)
+    }
+
+    fn from_state(mut state: GameState, player_token: Tile, turn: Turn) -> Game {
+        state.set_player_token(player_token);
+        state.set_first_turn(turn);
+
+        Game {
+            state,
         }
+    }
 
-        let mut board_state : HashMap<Direction, (usize, usize, usize)> = HashMap::new();
-
-        self.board.iter().for_each(|tile_entry| {
-            if let Some(fill) = tile_entry.1 {
-                let addin = {
-                    if *fill == self.ai_token {
-                        1
-                    } else {
-                        // This is just so that opponents tokens are recognized
-                        4
-                    }
-                };
-
-                let (x, y) = tile_entry.0;
-                let row = Direction::Row(*y);
-                let col = Direction::Column(*x);
-
-                board_state.entry(row).and_modify(|entry| {
-                    *entry = (entry.0 + x, entry.1 + y, entry.2 + addin)
-                }).or_insert((*x, *y, addin));
-
-                board_state.entry(col).and_modify(|entry| {
-                    *entry = (entry.0 + x, entry.1 + y, entry.2 + addin);
-                }).or_insert((*x, *y, addin));
-
-                // Diagonal Check
-                if (x + y) % 2 == 0 {
-                    // In the center case, we make changes to both the matching diagonal
-                    // and the unmatching diagonal
-                    // let match_diag = ;
-                    // let unmatch_diag = ;
-                    if *x == *y && *x == 1 {
-                        board_state.entry(Direction::MatchDiagonal).and_modify(|entry| {
-                            *entry = (entry.0 + x, entry.1 + y, entry.2 + addin);
-                        }).or_insert((*x, *y, addin));
-
-                        board_state.entry(Direction::UnmatchDiagonal).and_modify(|entry| {
-                            *entry = (entry.0 + x, entry.1 + y, entry.2 + addin);
-                        }).or_insert((*x, *y, addin));
-                    } 
-                    // In the matching diagonal case, we just add to the matching diagonal
-                    else if *x == *y && *x != addin {
-                        board_state.entry(Direction::MatchDiagonal).and_modify(|entry| {
-                            *entry = (entry.0 + x, entry.1 + y, entry.2 + addin);
-                        }).or_insert((*x, *y, addin));
-                    } 
-                    // In the unmatching diagonal case, we just add to the unmatching diagonal
-                    else {
-                        board_state.entry(Direction::UnmatchDiagonal).and_modify(|entry| {
-                            *entry = (entry.0 + x, entry.1 + y, entry.2 + addin);
-                        }).or_insert((*x, *y, addin));
-                    }
-                } 
-            }            
-        });
-
-        let ai_win : Vec<_> = board_state.iter().filter(|&direction| {
-            (*direction.1).2 == BOARD_SIZE * 1
-        }).collect();
+    /// Claims the tile at `(x, y)` for the player.
+    ///
+    /// Fails without changing the game if the tile can't be claimed, it is the engine's turn,
+    /// or the game is already over.
+    pub fn player_move(&mut self, x: usize, y: usize) -> Result<(), GameError> {
+        self.state.make_player_move(x, y)
+    }
 
-        let player_win : Vec<_> = board_state.iter().filter(|&direction| {
-            (*direction.1).2 == BOARD_SIZE * 4
-        }).collect();
+    /// How the rule-based engine picks between equally good tiles in this game
+    pub fn set_tie_break(&mut self, tie_break: TieBreak) {
+        self.state.set_tie_break(tie_break);
+    }
 
-        if ai_win.len() > 0 && player_win.len() > 0 {
-            panic!("How did that happen?!");
-        } else if ai_win.len() > 0 {
-            Some(Winner::AI)
-        } else if player_win.len() > 0 {
-            Some(Winner::Player)
-        } else {
-            None
+    /// Lets the rule-based engine choose and claim a tile, returning the tile it chose
+    pub fn ai_move(&mut self) -> Result<Position, GameError> {
+        self.ai_move_with(&mut RuleBased::new())
+    }
+
+    /// Lets `engine` choose and claim a tile for the AI, returning the tile it chose
+    pub fn ai_move_with<E: Engine>(&mut self, engine: &mut E) -> Result<Position, GameError> {
+        match self.turn() {
+            None => return Err(GameError::GameOver),
+            Some(Turn::Player) => return Err(GameError::NotYourTurn),
+            Some(Turn::AI) => (),
+        }
+
+        let ((x, y), strategy) = engine.choose_move_and_strategy(self)?;
+        self.state.make_move(Turn::AI, x, y, strategy)?;
+
+        Ok((x, y))
+    }
+
+    /// Lets the rule-based engine claim a tile for the AI like `ai_move`, returning the
+    /// explanation `explain_move` would give for it
+    ///
+    /// ```
+    /// use tictactoe::{Game, Strategy, Tile, Turn};
+    ///
+    /// let mut game = Game::with_options(Tile::X, Turn::Player);
+    /// game.player_move(0, 0).unwrap();
+    /// game.ai_move().unwrap();
+    /// game.player_move(1, 0).unwrap();
+    ///
+    /// let explanation = game.ai_move_explained().unwrap();
+    /// assert_eq!(explanation.position, (2, 0));
+    /// assert_eq!(game.history().last().unwrap().strategy, Some(Strategy::Block));
+    /// ```
+    pub fn ai_move_explained(&mut self) -> Result<Explanation, GameError> {
+        match self.turn() {
+            None => return Err(GameError::GameOver),
+            Some(Turn::Player) => return Err(GameError::NotYourTurn),
+            Some(Turn::AI) => (),
         }
+
+        let explanation = self.explain_move()?;
+        let (x, y) = explanation.position;
+        self.state.make_move(Turn::AI, x, y, Some(explanation.strategy))?;
+
+        Ok(explanation)
     }
 
-    fn make_ai_move(&mut self, x: usize, y: usize) -> Result<(), String> {
-        self.last_move = Some((x, y));
-        self.filled_tiles += 1;
-        self.board.make_move(x, y, self.ai_token)
+    /// Claims the tile at `(x, y)` for the AI, for replaying a move chosen elsewhere
+    pub fn ai_move_at(&mut self, x: usize, y: usize) -> Result<(), GameError> {
+        self.state.make_move(Turn::AI, x, y, None)
     }
 
-    fn make_player_move(&mut self, x: usize, y: usize) -> Result<(), String> {
-        self.last_move = Some((x, y));
-        self.filled_tiles += 1;
-        self.board.make_move(x, y, self.player_token)
+    /// Takes back the last move made by either side, returning it. Undone moves can be
+    /// redone until another move is made.
+    ///
+    /// ```
+    /// use tictactoe::{Game, Strategy, Tile, Turn};
+    ///
+    /// let mut game = Game::with_options(Tile::X, Turn::Player);
+    /// game.player_move(0, 0).unwrap();
+    /// game.ai_move().unwrap();
+    ///
+    /// let undone = game.undo().unwrap();
+    /// assert_eq!(undone.strategy, Some(Strategy::Center));
+    /// assert_eq!(game.turn(), Some(Turn::AI));
+    ///
+    /// game.redo().unwrap();
+    /// assert_eq!(game.history().len(), 2);
+    /// assert_eq!(game.board().tile(1, 1), Some(Tile::O));
+    /// ```
+    pub fn undo(&mut self) -> Option<Move> {
+        self.state.undo()
+    }
+
+    /// Plays the last undone move again, returning it, or `None` if there is nothing to redo
+    /// or the move can't be played
+    pub fn redo(&mut self) -> Option<Move> {
+        self.state.redo()
+    }
+
+    /// Every move made so far, oldest first
+    pub fn history(&self) -> &[Move] {
+        &self.state.history
+    }
+
+    /// The side expected to make the next move, or `None` once the game is over
+    pub fn turn(&self) -> Option<Turn> {
+        match self.state.status {
+            GameStatus::InProgress => Some(self.state.turn),
+            _ => None,
+        }
+    }
+
+    /// Whether the game is still being played, and how it ended if not
+    pub fn result(&self) -> GameStatus {
+        self.state.status
+    }
+
+    /// The token used by the player
+    pub fn player_token(&self) -> Tile {
+        self.state.player_token
+    }
+
+    /// The token used by the engine
+    pub fn ai_token(&self) -> Tile {
+        self.state.ai_token
+    }
+
+    /// The token of the side expected to make the next move, or `None` once the game is over
+    pub fn token_to_move(&self) -> Option<Tile> {
+        self.turn().map(|turn| match turn {
+            Turn::AI => self.state.ai_token,
+            Turn::Player => self.state.player_token,
+        })
+    }
+
+    /// The tile the rule-based engine would claim for the side expected to move next, the
+    /// strategy which picked it and the lines or tiles behind that choice.
+    ///
+    /// ```
+    /// use tictactoe::{Evidence, Game, Strategy, Tile, Turn};
+    ///
+    /// let mut game = Game::with_options(Tile::X, Turn::Player);
+    /// game.player_move(0, 0).unwrap();
+    /// game.ai_move().unwrap();
+    /// game.player_move(1, 0).unwrap();
+    ///
+    /// let explanation = game.explain_move().unwrap();
+    /// assert_eq!(explanation.position, (2, 0));
+    /// assert_eq!(explanation.strategy, Strategy::Block);
+    /// assert!(matches!(explanation.evidence, Evidence::Blocks(line) if line.start == (0, 0)));
+    /// ```
+    pub fn explain_move(&self) -> Result<Explanation, GameError> {
+        RuleBased::new().explain_move(self)
+    }
+
+    /// Hints for the player: every empty tile ranked by the rule-based engine playing from the
+    /// player's side, best first, each with the strategy and evidence behind it.
+    ///
+    /// ```
+    /// use tictactoe::{Game, Strategy, Tile, Turn};
+    ///
+    /// let mut game = Game::with_options(Tile::X, Turn::AI);
+    /// game.ai_move().unwrap();
+    ///
+    /// let hints = game.suggest_player_move().unwrap();
+    /// // The engine opened in the center, so the best reply is a corner
+    /// assert_eq!(hints.len(), 8);
+    /// assert_eq!(hints[0].strategy, Strategy::EmptyCorner);
+    /// assert_eq!(hints[0].to_string(), "(0, 0) takes an empty corner");
+    /// ```
+    pub fn suggest_player_move(&self) -> Result<Vec<Explanation>, GameError> {
+        match self.turn() {
+            None => Err(GameError::GameOver),
+            Some(Turn::AI) => Err(GameError::NotYourTurn),
+            Some(Turn::Player) => Ok(self.state.flipped().suggest_moves()),
+        }
+    }
+
+    /// The win, draw or loss reached by claiming each empty tile, for the side expected to
+    /// move next, assuming perfect play from there on.
+    ///
+    /// Every tile is searched to the end of the game with no time or node limit, so this is
+    /// meant for small boards such as 3x3, or 4x4 once a few tiles are claimed. Larger boards
+    /// can take far too long; `Minimax::search_within` gives up at a deadline instead.
+    ///
+    /// ```
+    /// use tictactoe::{Game, Tile, Turn};
+    /// use tictactoe::engine::Value;
+    ///
+    /// let mut game = Game::with_options(Tile::X, Turn::Player);
+    /// game.player_move(0, 0).unwrap();
+    ///
+    /// let evaluation = game.evaluate().unwrap();
+    /// assert_eq!(evaluation.token(), Tile::O);
+    /// assert_eq!(evaluation.best_moves(), vec![(1, 1)]);
+    /// assert_eq!(evaluation.value(1, 0), Some(Value::Loss(6)));
+    /// ```
+    pub fn evaluate(&self) -> Result<Evaluation, GameError> {
+        let token = self.token_to_move().ok_or(GameError::GameOver)?;
+
+        Ok(Minimax::new().evaluate(self.board(), token))
+    }
+
+    /// A read-only view of the board
+    pub fn board(&self) -> &Board {
+        &self.state.board
+    }
+
+    pub(crate) fn state(&self) -> &GameState {
+        &self.state
     }
 }
 
@@ -433,12 +783,13 @@ mod tests {
         use super::*;
 
         let mut game = GameState::new();
+        game.set_first_turn(Turn::AI);
 
-        game.make_ai_move(0, 0);
-        game.make_player_move(0, 2);
-        game.make_ai_move(0, 1);
-        game.make_player_move(1, 1);
-        game.make_ai_move(2,0);
+        game.make_ai_move(0, 0).unwrap();
+        game.make_player_move(0, 2).unwrap();
+        game.make_ai_move(0, 1).unwrap();
+        game.make_player_move(1, 1).unwrap();
+        game.make_ai_move(2,0).unwrap();
 
         if let Some(tuple) = game.check_win_block_condition(CheckType::Win) {
             let (x, y) = tuple;
@@ -454,9 +805,11 @@ mod tests {
         use super::*;
 
         let mut game = GameState::new();
+        game.set_first_turn(Turn::AI);
 
-        game.make_ai_move(0, 0);
-        game.make_ai_move(1, 0);
+        game.make_ai_move(0, 0).unwrap();
+        game.make_player_move(1, 2).unwrap();
+        game.make_ai_move(1, 0).unwrap();
 
         if let Some(tuple) = game.check_win_block_condition(CheckType::Win) {
             let (x, y) = tuple;
@@ -472,9 +825,11 @@ mod tests {
         use super::*;
 
         let mut game = GameState::new();
+        game.set_first_turn(Turn::AI);
 
-        game.make_ai_move(0, 0);
-        game.make_ai_move(1, 1);
+        game.make_ai_move(0, 0).unwrap();
+        game.make_player_move(1, 0).unwrap();
+        game.make_ai_move(1, 1).unwrap();
 
         if let Some(tuple) = game.check_win_block_condition(CheckType::Win) {
             let (x, y) = tuple;
@@ -490,9 +845,11 @@ mod tests {
         use super::*;
 
         let mut game = GameState::new();
+        game.set_first_turn(Turn::AI);
 
-        game.make_ai_move(2, 0);
-        game.make_ai_move(1, 1);
+        game.make_ai_move(2, 0).unwrap();
+        game.make_player_move(0, 0).unwrap();
+        game.make_ai_move(1, 1).unwrap();
 
         if let Some(tuple) = game.check_win_block_condition(CheckType::Win) {
             let (x, y) = tuple;
@@ -509,8 +866,9 @@ mod tests {
 
         let mut game = GameState::new();
 
-        game.make_player_move(0, 0);
-        game.make_player_move(0, 1);
+        game.make_player_move(0, 0).unwrap();
+        game.make_ai_move(1, 1).unwrap();
+        game.make_player_move(0, 1).unwrap();
 
         if let Some(tuple) = game.check_win_block_condition(CheckType::Block) {
             let (x, y) = tuple;
@@ -527,8 +885,9 @@ mod tests {
 
         let mut game = GameState::new();
 
-        game.make_player_move(0, 0);
-        game.make_player_move(1, 0);
+        game.make_player_move(0, 0).unwrap();
+        game.make_ai_move(1, 1).unwrap();
+        game.make_player_move(1, 0).unwrap();
 
         if let Some(tuple) = game.check_win_block_condition(CheckType::Block) {
             let (x, y) = tuple;
@@ -545,8 +904,9 @@ mod tests {
 
         let mut game = GameState::new();
 
-        game.make_player_move(0, 0);
-        game.make_player_move(1, 1);
+        game.make_player_move(0, 0).unwrap();
+        game.make_ai_move(1, 0).unwrap();
+        game.make_player_move(1, 1).unwrap();
 
         if let Some(tuple) = game.check_win_block_condition(CheckType::Block) {
             let (x, y) = tuple;
@@ -563,8 +923,9 @@ mod tests {
 
         let mut game = GameState::new();
 
-        game.make_player_move(2, 0);
-        game.make_player_move(1, 1);
+        game.make_player_move(2, 0).unwrap();
+        game.make_ai_move(1, 0).unwrap();
+        game.make_player_move(1, 1).unwrap();
 
         if let Some(tuple) = game.check_win_block_condition(CheckType::Block) {
             let (x, y) = tuple;
@@ -579,7 +940,7 @@ mod tests {
     fn test_check_center() {
         use super::*;
 
-        let mut game = GameState::new();
+        let game = GameState::new();
 
         if let Some(tuple) = game.check_center() {
             let (x, y) = tuple;
@@ -596,7 +957,7 @@ mod tests {
 
         let mut game = GameState::new();
 
-        game.make_player_move(0, 0);
+        game.make_player_move(0, 0).unwrap();
 
         if let Some(tuple) = game.check_corner() {
             let (x, y) = tuple;
@@ -613,7 +974,7 @@ mod tests {
 
         let mut game = GameState::new();
 
-        game.make_player_move(2, 0);
+        game.make_player_move(2, 0).unwrap();
 
         if let Some(tuple) = game.check_corner() {
             let (x, y) = tuple;
@@ -629,11 +990,12 @@ mod tests {
         use super::*;
 
         let mut game = GameState::new();
+        game.set_first_turn(Turn::AI);
 
-        game.make_ai_move(0, 0);
-        game.make_player_move(1, 0);
-        game.make_ai_move(2, 0);
-        game.make_player_move(0, 2);
+        game.make_ai_move(0, 0).unwrap();
+        game.make_player_move(1, 0).unwrap();
+        game.make_ai_move(2, 0).unwrap();
+        game.make_player_move(0, 2).unwrap();
 
         if let Some(tuple) = game.check_fork_block_condition(CheckType::Win) {
             let (x, y) = tuple;
@@ -651,10 +1013,10 @@ mod tests {
 
         let mut game = GameState::new();
 
-        game.make_player_move(0, 0);
-        game.make_ai_move(1, 0);
-        game.make_player_move(2, 0);
-        game.make_ai_move(0, 2);
+        game.make_player_move(0, 0).unwrap();
+        game.make_ai_move(1, 0).unwrap();
+        game.make_player_move(2, 0).unwrap();
+        game.make_ai_move(0, 2).unwrap();
 
         if let Some(tuple) = game.check_fork_block_condition(CheckType::Block) {
             let (x, y) = tuple;
@@ -665,4 +1027,396 @@ mod tests {
             panic!()
         }
     }
-}
\ No newline at end of file
+
+    #[test]
+    fn test_find_ai_move_block_fork_forces() {
+        use super::*;
+
+        let mut game = GameState::new();
+
+        game.set_player_token(Tile::X);
+        game.make_player_move(0, 0).unwrap();
+        game.make_ai_move(1, 1).unwrap();
+        game.make_player_move(2, 2).unwrap();
+
+        // Either opposite corner hands the player a fork, so the AI must take a side
+        if let Ok(tuple) = game.find_ai_move() {
+            let (x, y) = tuple;
+            assert_eq!((x + y) % 2, 1);
+        } else {
+            panic!()
+        }
+    }
+
+    #[test]
+    fn test_game_turn() {
+        use super::*;
+
+        let mut game = Game::with_options(Tile::X, Turn::AI);
+        assert_eq!(game.turn(), Some(Turn::AI));
+
+        let (x, y) = game.ai_move().unwrap();
+        assert_eq!(game.board().tile(x, y), Some(Tile::O));
+        assert_eq!(game.turn(), Some(Turn::Player));
+    }
+
+    #[test]
+    fn test_game_won() {
+        use super::*;
+        use crate::conditions::{Direction, Line};
+
+        let mut game = GameState::new();
+        game.set_first_turn(Turn::AI);
+
+        game.make_ai_move(0, 0).unwrap();
+        game.make_player_move(0, 1).unwrap();
+        game.make_ai_move(1, 0).unwrap();
+        game.make_player_move(1, 1).unwrap();
+        game.make_ai_move(2, 0).unwrap();
+
+        assert_eq!(game.status, GameStatus::Won { by: Winner::AI, line: Line { start: (0, 0), direction: Direction::Row, length: 3 } });
+        assert_eq!(game.make_player_move(2, 2), Err(GameError::GameOver));
+        assert_eq!(game.find_ai_move(), Err(GameError::GameOver));
+    }
+
+    #[test]
+    fn test_game_draw() {
+        use super::*;
+
+        let mut game = GameState::new();
+
+        for &(x, y) in &[(0, 0), (1, 1), (2, 2), (0, 1), (2, 1), (2, 0), (0, 2), (1, 2), (1, 0)] {
+            if game.turn == Turn::Player {
+                game.make_player_move(x, y).unwrap();
+            } else {
+                game.make_ai_move(x, y).unwrap();
+            }
+        }
+
+        assert_eq!(game.status, GameStatus::Draw);
+    }
+
+    #[test]
+    fn test_rejected_move_keeps_state() {
+        use super::*;
+
+        let mut game = GameState::new();
+
+        game.make_player_move(1, 1).unwrap();
+        assert_eq!(game.make_player_move(0, 0), Err(GameError::NotYourTurn));
+        assert_eq!(game.make_ai_move(1, 1), Err(GameError::Occupied { x: 1, y: 1, by: Tile::O }));
+        assert_eq!(game.make_ai_move(3, 3), Err(GameError::OutOfBounds { x: 3, y: 3 }));
+
+        assert_eq!(game.filled_tiles, 1);
+        assert_eq!(game.last_move, Some((1, 1)));
+        assert_eq!(game.turn, Turn::AI);
+    }
+
+    #[test]
+    fn test_larger_board() {
+        use super::*;
+        use crate::conditions::{Direction, Line};
+
+        let mut game = GameState::with_board(Board::with_size(4, 4, 4).unwrap());
+        game.set_first_turn(Turn::AI);
+
+        game.make_ai_move(0, 0).unwrap();
+        game.make_player_move(3, 0).unwrap();
+        game.make_ai_move(1, 1).unwrap();
+        game.make_player_move(3, 1).unwrap();
+        game.make_ai_move(2, 2).unwrap();
+        game.make_player_move(0, 3).unwrap();
+
+        // The AI takes its own win before blocking the player's column
+        assert_eq!(game.find_ai_move(), Ok((3, 3)));
+        game.make_ai_move(3, 3).unwrap();
+        assert!(matches!(game.status, GameStatus::Won { by: Winner::AI, line: Line { direction: Direction::MatchDiagonal, .. } }));
+    }
+
+    #[test]
+    fn test_larger_board_blocks() {
+        use super::*;
+
+        let mut game = GameState::with_board(Board::with_size(6, 5, 4).unwrap());
+
+        game.make_player_move(1, 4).unwrap();
+        game.make_ai_move(0, 0).unwrap();
+        game.make_player_move(2, 4).unwrap();
+        game.make_ai_move(5, 0).unwrap();
+        game.make_player_move(3, 4).unwrap();
+
+        // Either end completes the row, and both are legal
+        let (x, y) = game.find_ai_move().unwrap();
+        assert_eq!(y, 4);
+        assert!(x == 0 || x == 4);
+    }
+
+    #[test]
+    fn test_seeded_tie_break() {
+        use super::*;
+
+        let play = |seed| {
+            let mut game = Game::with_options(Tile::O, Turn::Player);
+            game.set_tie_break(TieBreak::Random { seed });
+            game.player_move(1, 1).unwrap();
+            game.ai_move().unwrap()
+        };
+
+        // Any corner answers the center, but a seed always picks the same one
+        for seed in 0..8 {
+            let (x, y) = play(seed);
+            assert!(x != 1 && y != 1);
+            assert_eq!(play(seed), (x, y));
+        }
+    }
+
+    #[test]
+    fn test_observed_events() {
+        use super::*;
+
+        let mut game = GameState::new();
+        game.make_player_move(0, 0).unwrap();
+
+        let mut events = Vec::new();
+        let found = game.find_ai_move_observed(&mut |event: &Event| events.push(*event)).unwrap();
+
+        assert_eq!(found, (1, 1));
+        assert_eq!(events, vec![
+            Event::StageEntered(Strategy::Win),
+            Event::StageEntered(Strategy::Block),
+            Event::StageEntered(Strategy::Fork),
+            Event::StageEntered(Strategy::BlockFork),
+            Event::StageEntered(Strategy::Center),
+            Event::CandidateFound { strategy: Strategy::Center, position: (1, 1) },
+            Event::MoveChosen { strategy: Strategy::Center, position: (1, 1) },
+        ]);
+
+        // Every corner is a candidate once the center is gone, and the tie break picks one
+        let mut game = GameState::new();
+        game.make_player_move(1, 1).unwrap();
+
+        let mut events = Vec::new();
+        let found = game.find_ai_move_observed(&mut |event: &Event| events.push(*event)).unwrap();
+
+        let candidates : Vec<_> = events.iter().filter_map(|event| match event {
+            Event::CandidateFound { strategy: Strategy::EmptyCorner, position } => Some(*position),
+            _ => None,
+        }).collect();
+        assert_eq!(candidates, vec![(0, 0), (2, 0), (0, 2), (2, 2)]);
+        assert_eq!(events.last(), Some(&Event::MoveChosen { strategy: Strategy::EmptyCorner, position: found }));
+    }
+
+    #[test]
+    fn test_explain_fork() {
+        use super::*;
+        use crate::conditions::Direction;
+
+        let mut game = GameState::new();
+        game.set_first_turn(Turn::AI);
+
+        game.make_ai_move(0, 0).unwrap();
+        game.make_player_move(1, 0).unwrap();
+        game.make_ai_move(2, 0).unwrap();
+        game.make_player_move(0, 2).unwrap();
+
+        // Taking (2, 2) threatens both the right column and the diagonal
+        let explanation = game.explain_ai_move(&mut |_: &Event| ()).unwrap();
+        assert_eq!(explanation.position, (2, 2));
+        assert_eq!(explanation.strategy, Strategy::Fork);
+        assert_eq!(explanation.evidence, Evidence::Fork(vec![
+            Line { start: (2, 0), direction: Direction::Column, length: 3 },
+            Line { start: (0, 0), direction: Direction::MatchDiagonal, length: 3 },
+        ]));
+    }
+
+    #[test]
+    fn test_explain_block_fork() {
+        use super::*;
+
+        let mut game = GameState::new();
+        game.set_player_token(Tile::X);
+
+        game.make_player_move(0, 0).unwrap();
+        game.make_ai_move(1, 1).unwrap();
+        game.make_player_move(2, 2).unwrap();
+
+        let explanation = game.explain_ai_move(&mut |_: &Event| ()).unwrap();
+        assert_eq!(explanation.strategy, Strategy::BlockFork);
+        if let Evidence::BlockFork { forks, forcing } = explanation.evidence {
+            assert_eq!(forks, vec![(2, 0), (0, 2)]);
+            assert!(forcing.is_some());
+        } else {
+            panic!()
+        }
+
+        // Taking a single fork away doesn't force anything, even when the tile opens a line
+        let mut game = GameState::new();
+        game.set_player_token(Tile::X);
+
+        game.make_player_move(1, 0).unwrap();
+        game.make_ai_move(1, 1).unwrap();
+        game.make_player_move(0, 1).unwrap();
+
+        let explanation = game.explain_ai_move(&mut |_: &Event| ()).unwrap();
+        assert_eq!(explanation.evidence, Evidence::BlockFork { forks: vec![(0, 0)], forcing: None });
+        assert_eq!(explanation.to_string(), "(0, 0) takes away the opponent's fork");
+
+        // Without the opponent's corner to point at, the opposite corner is still named
+        let explanation = Explanation { position: (2, 2), strategy: Strategy::OppositeCorner, evidence: Evidence::Placement };
+        assert_eq!(explanation.to_string(), "(2, 2) takes the opposite corner");
+    }
+
+    #[test]
+    fn test_suggest_moves_ranks_block_first() {
+        use super::*;
+
+        let mut game = Game::with_options(Tile::O, Turn::AI);
+        game.state.make_ai_move(0, 0).unwrap();
+        game.player_move(1, 1).unwrap();
+        game.state.make_ai_move(1, 0).unwrap();
+
+        let hints = game.suggest_player_move().unwrap();
+        assert_eq!(hints.len(), 6);
+        assert_eq!(hints[0].position, (2, 0));
+        assert_eq!(hints[0].strategy, Strategy::Block);
+        assert_eq!(hints[0].to_string(), "(2, 0) blocks the opponent's row from (0, 0)");
+
+        let mut positions : Vec<_> = hints.iter().map(|hint| hint.position).collect();
+        positions.sort_by_key(|&(x, y)| (y, x));
+        assert_eq!(positions, game.board().empty_tiles());
+
+        assert_eq!(game.ai_move().unwrap_err(), GameError::NotYourTurn);
+    }
+
+    #[test]
+    fn test_suggest_moves_labels_every_forcing_move() {
+        use super::*;
+
+        // O has two forks, so X has to force a reply. (0, 2) is forcing and one of the fork
+        // tiles, while (1, 0) and (2, 0) are only forcing
+        let board : Board = "X../.OX/..O".parse().unwrap();
+        let game = Game::with_board(board, Tile::X, Turn::Player);
+
+        let hints = game.suggest_player_move().unwrap();
+        let block_forks : Vec<_> = hints.iter()
+            .take_while(|hint| hint.strategy == Strategy::BlockFork)
+            .map(|hint| hint.position)
+            .collect();
+        assert_eq!(block_forks.len(), 3);
+        assert_eq!(block_forks[0], (0, 2));
+    }
+
+    #[test]
+    fn test_undo_redo() {
+        use super::*;
+
+        let mut game = Game::with_options(Tile::X, Turn::Player);
+        game.player_move(0, 0).unwrap();
+        game.ai_move().unwrap();
+        game.player_move(2, 2).unwrap();
+        assert_eq!(game.state.last_move, Some((2, 2)));
+
+        // Undoing back to before the player's corner leaves the AI answering the first one
+        let undone = game.undo().unwrap();
+        assert_eq!(undone, Move { turn: Turn::Player, token: Tile::X, position: (2, 2), strategy: None });
+        assert_eq!(game.state.last_move, Some((1, 1)));
+        assert_eq!(game.state.filled_tiles, 2);
+        assert_eq!(game.turn(), Some(Turn::Player));
+        assert_eq!(game.board().tile(2, 2), None);
+
+        game.undo().unwrap();
+        assert_eq!(game.state.last_move, Some((0, 0)));
+        assert_eq!(game.turn(), Some(Turn::AI));
+
+        game.redo().unwrap();
+        game.redo().unwrap();
+        assert_eq!(game.redo(), None);
+        assert_eq!(game.history().iter().map(|mv| mv.position).collect::<Vec<_>>(), vec![(0, 0), (1, 1), (2, 2)]);
+
+        // A new move drops whatever was left to redo
+        game.undo().unwrap();
+        game.player_move(2, 0).unwrap();
+        assert_eq!(game.redo(), None);
+
+        // Undo also reopens a finished game
+        while let Some(turn) = game.turn() {
+            match turn {
+                Turn::AI => {
+                    game.ai_move().unwrap();
+                },
+                Turn::Player => {
+                    let (x, y) = game.board().empty_tiles()[0];
+                    game.player_move(x, y).unwrap();
+                },
+            }
+        }
+        game.undo().unwrap();
+        assert_eq!(game.result(), GameStatus::InProgress);
+        assert_eq!(game.history().len(), game.board().filled_tiles());
+
+        while game.undo().is_some() {}
+        assert_eq!(game.board().filled_tiles(), 0);
+        assert_eq!(game.state.last_move, None);
+        assert_eq!(game.turn(), Some(Turn::Player));
+
+        // A move which can no longer be played isn't reported as redone
+        let mut game = Game::with_options(Tile::X, Turn::Player);
+        game.player_move(0, 0).unwrap();
+        game.undo().unwrap();
+        game.state.undone[0].turn = Turn::AI;
+        assert_eq!(game.redo(), None);
+        assert_eq!(game.state.undone.len(), 1);
+        assert!(game.history().is_empty());
+    }
+
+    #[cfg(feature = "serde")]
+    #[test]
+    fn test_serde_json() {
+        use super::*;
+
+        let mut game = Game::with_options(Tile::X, Turn::Player);
+        game.set_tie_break(TieBreak::Random { seed: 5 });
+        game.player_move(0, 0).unwrap();
+        game.ai_move().unwrap();
+        game.player_move(2, 2).unwrap();
+        game.undo().unwrap();
+
+        let json = serde_json::to_value(&game).unwrap();
+        assert_eq!(json["turn"], "Player");
+        assert_eq!(json["status"], "InProgress");
+        assert_eq!(json["history"][1], serde_json::json!({ "turn": "AI", "token": "O", "position": [1, 1], "strategy": "Center" }));
+        assert_eq!(json["undone"][0]["position"], serde_json::json!([2, 2]));
+
+        let mut read : Game = serde_json::from_value(json.clone()).unwrap();
+        assert_eq!(read.to_string(), game.to_string());
+        assert_eq!(read.history(), game.history());
+        assert_eq!(read.state.last_move, Some((1, 1)));
+        assert_eq!(read.state.filled_tiles, 2);
+        read.redo().unwrap();
+        assert_eq!(read.board().tile(2, 2), Some(Tile::X));
+
+        let mut broken = json.clone();
+        broken["status"] = serde_json::json!("Draw");
+        assert!(serde_json::from_value::<Game>(broken).is_err());
+
+        // Stale turns and undone moves which can't be redone are refused too
+        let mut broken = json.clone();
+        broken["turn"] = serde_json::json!("AI");
+        assert!(serde_json::from_value::<Game>(broken).is_err());
+
+        for undone in &[
+            serde_json::json!({ "turn": "AI", "token": "O", "position": [2, 2], "strategy": null }),
+            serde_json::json!({ "turn": "Player", "token": "X", "position": [1, 1], "strategy": null }),
+            serde_json::json!({ "turn": "Player", "token": "O", "position": [2, 2], "strategy": null }),
+        ] {
+            let mut broken = json.clone();
+            broken["undone"] = serde_json::json!([undone]);
+            assert!(serde_json::from_value::<Game>(broken).is_err(), "{}", undone);
+        }
+
+        assert!(Game::try_from(GameData::from(game.clone())).is_ok());
+        let mut data = GameData::from(game);
+        data.history.swap(0, 1);
+        assert_eq!(Game::try_from(data).map(|_| ()), Err(GameError::InconsistentSave));
+    }
+}
@@ -0,0 +1,19 @@
+use crate::board::Position;
+use crate::conditions::Turn;
+use crate::strategy::Strategy;
+use crate::tile::Tile;
+
+#[cfg(feature = "serde")]
+use serde::{Deserialize, Serialize};
+
+/// A move made during a game
+#[derive(Debug, PartialEq, Copy, Clone)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
+pub struct Move {
+    /// The side which made the move
+    pub turn: Turn,
+    pub token: Tile,
+    pub position: Position,
+    /// The stage of the rule-based engine which picked the tile, when it was picked by one
+    pub strategy: Option<Strategy>,
+}
@@ -1,6 +1,30 @@
+mod bitmask;
 mod board;
 mod tile;
 mod strategy;
 mod conditions;
+mod error;
+mod explain;
+mod history;
+mod notation;
+mod observer;
+mod rng;
+mod symmetry;
+pub mod engine;
 pub mod game;
+pub mod record;
+pub mod selfplay;
+pub mod verify;
 
+pub use crate::board::{Board, Position};
+pub use crate::tile::Tile;
+pub use crate::conditions::{Direction, GameStatus, Line, Turn, Winner};
+pub use crate::engine::{Engine, TieBreak};
+pub use crate::error::GameError;
+pub use crate::explain::{Evidence, Explanation};
+pub use crate::history::Move;
+pub use crate::notation::ParseError;
+pub use crate::observer::{Event, Observer};
+pub use crate::strategy::Strategy;
+pub use crate::symmetry::Transform;
+pub use crate::game::Game;
@@ -0,0 +1,286 @@
+//! A compact text form for positions, in the spirit of chess FEN.
+//!
+//! A board is written row by row from the top, with rows separated by `/` and each tile
+//! written as `X`, `O` or `.` when empty, so `X.O/.X./..O` is a 3x3 board. The win length
+//! follows after a `:` when it isn't the length of the board's shorter side, as in
+//! `...../...../...../.....:4`.
+//!
+//! A game adds two fields after the board, separated by spaces: the token to move next, or
+//! `-` once the game is over, and the token used by the player. `X.O/.X./... O X` is a game
+//! where O is to move and the player is using X.
+
+use crate::board::Board;
+use crate::conditions::Turn;
+use crate::error::GameError;
+use crate::game::Game;
+use crate::tile::Tile;
+
+use std::error::Error;
+use std::fmt;
+use std::str::FromStr;
+
+/// Reasons text can't be read as a board or game
+#[derive(Debug, PartialEq, Copy, Clone)]
+pub enum ParseError {
+    /// A field is missing, or there are more fields than expected
+    WrongFieldCount,
+    /// A tile which isn't `X`, `O` or `.`
+    InvalidTile(char),
+    /// A token which isn't `X` or `O`, or `-` where allowed
+    InvalidToken,
+    /// The win length isn't a number
+    InvalidWinLength,
+    /// Rows don't all have the same number of tiles
+    UnevenRows,
+    /// The board dimensions can't be played on
+    InvalidSize(GameError),
+    /// The tokens can't have taken turns to reach these counts
+    PieceCount { x: usize, o: usize },
+    /// Both tokens have completed a line, or one token has completed lines which no
+    /// single move could finish together
+    TooManyWinners,
+    /// The token which completed a line wasn't the last to move
+    WinnerNotLast(Tile),
+    /// The token to move doesn't match the counts, or whether the game is over
+    WrongTokenToMove,
+}
+
+impl fmt::Display for ParseError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            ParseError::WrongFieldCount => write!(f, "Wrong number of fields"),
+            ParseError::InvalidTile(tile) => write!(f, "'{}' is not a tile, expected X, O or .", tile),
+            ParseError::InvalidToken => write!(f, "Expected a token of X or O"),
+            ParseError::InvalidWinLength => write!(f, "Win length is not a number"),
+            ParseError::UnevenRows => write!(f, "Rows have different lengths"),
+            ParseError::InvalidSize(error) => write!(f, "{}", error),
+            ParseError::PieceCount { x, o } => write!(f, "{} X and {} O can't be reached by taking turns", x, o),
+            ParseError::TooManyWinners => write!(f, "More lines are completed than one move can finish"),
+            ParseError::WinnerNotLast(tile) => write!(f, "{:?} completed a line but didn't move last", tile),
+            ParseError::WrongTokenToMove => write!(f, "The token to move doesn't match the board"),
+        }
+    }
+}
+
+impl Error for ParseError {}
+
+impl fmt::Display for Board {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        for y in 0..self.height() {
+            if y > 0 {
+                write!(f, "/")?;
+            }
+
+            for x in 0..self.width() {
+                match self.tile(x, y) {
+                    Some(tile) => write!(f, "{:?}", tile)?,
+                    None => write!(f, ".")?,
+                }
+            }
+        }
+
+        if self.win_length() != self.width().min(self.height()) {
+            write!(f, ":{}", self.win_length())?;
+        }
+
+        Ok(())
+    }
+}
+
+/// Reads a board, rejecting any which couldn't be reached by the tokens taking turns
+impl FromStr for Board {
+    type Err = ParseError;
+
+    fn from_str(text: &str) -> Result<Board, ParseError> {
+        let board = parse_tiles(text)?;
+        validate(&board)?;
+
+        Ok(board)
+    }
+}
+
+// Reads the tiles of a board without checking they could have been reached, for positions
+// which were set up by hand
+pub(crate) fn parse_tiles(text: &str) -> Result<Board, ParseError> {
+    let (tiles, win_length) = match text.trim().split_once(':') {
+        Some((tiles, win_length)) => (tiles, Some(win_length.parse().map_err(|_| ParseError::InvalidWinLength)?)),
+        None => (text.trim(), None),
+    };
+
+    let rows : Vec<&str> = tiles.split('/').collect();
+    let width = rows[0].chars().count();
+    if rows.iter().any(|row| row.chars().count() != width) {
+        return Err(ParseError::UnevenRows)
+    }
+
+    let height = rows.len();
+    let win_length = win_length.unwrap_or_else(|| width.min(height));
+    let mut board = Board::with_size(width, height, win_length).map_err(ParseError::InvalidSize)?;
+
+    for (y, row) in rows.iter().enumerate() {
+        for (x, tile) in row.chars().enumerate() {
+            let token = match tile {
+                'X' | 'x' => Tile::X,
+                'O' | 'o' => Tile::O,
+                '.' => continue,
+                _ => return Err(ParseError::InvalidTile(tile)),
+            };
+
+            // Every tile is visited once, on a board of the right size
+            let _ = board.make_move(x, y, token);
+        }
+    }
+
+    Ok(board)
+}
+
+// Checks the board could have been reached by the tokens taking turns from an empty board
+fn validate(board: &Board) -> Result<(), ParseError> {
+    let x = board.count(Tile::X);
+    let o = board.count(Tile::O);
+    if x > o + 1 || o > x + 1 {
+        return Err(ParseError::PieceCount { x, o })
+    }
+
+    let x_lines = board.completed_lines(Tile::X);
+    let o_lines = board.completed_lines(Tile::O);
+    if !x_lines.is_empty() && !o_lines.is_empty() {
+        return Err(ParseError::TooManyWinners)
+    }
+
+    for &(token, ref lines) in &[(Tile::X, x_lines), (Tile::O, o_lines)] {
+        if lines.is_empty() {
+            continue
+        }
+
+        // The game stops at the first completed line, so every line the winner holds has to
+        // run through the tile they claimed last
+        let last_tiles = lines.iter().skip(1).fold(board.line_tiles(&lines[0]), |mut shared, line| {
+            let tiles = board.line_tiles(line);
+            shared.retain(|tile| tiles.contains(tile));
+            shared
+        });
+        if last_tiles.is_empty() {
+            return Err(ParseError::TooManyWinners)
+        }
+
+        if board.count(token) < board.count(token.other()) {
+            return Err(ParseError::WinnerNotLast(token))
+        }
+    }
+
+    Ok(())
+}
+
+fn parse_token(text: &str) -> Result<Tile, ParseError> {
+    match text {
+        "X" | "x" => Ok(Tile::X),
+        "O" | "o" => Ok(Tile::O),
+        _ => Err(ParseError::InvalidToken),
+    }
+}
+
+impl fmt::Display for Game {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self.token_to_move() {
+            Some(token) => write!(f, "{} {:?} {:?}", self.board(), token, self.player_token()),
+            None => write!(f, "{} - {:?}", self.board(), self.player_token()),
+        }
+    }
+}
+
+/// Reads a game, rejecting positions which couldn't be reached or where the token to move
+/// doesn't fit the board
+impl FromStr for Game {
+    type Err = ParseError;
+
+    fn from_str(text: &str) -> Result<Game, ParseError> {
+        let fields : Vec<&str> = text.split_whitespace().collect();
+        if fields.len() != 3 {
+            return Err(ParseError::WrongFieldCount)
+        }
+
+        let board : Board = fields[0].parse()?;
+        let player_token = parse_token(fields[2])?;
+        let to_move = match fields[1] {
+            "-" => None,
+            token => Some(parse_token(token)?),
+        };
+
+        let x = board.count(Tile::X);
+        let o = board.count(Tile::O);
+        let finished = board.winner().is_some() || board.is_full();
+        let to_move = match to_move {
+            None if finished => {
+                // Nobody moves again, so either side will do
+                if x > o { Tile::O } else { Tile::X }
+            },
+            Some(token) if !finished && board.count(token) <= board.count(token.other()) => token,
+            _ => return Err(ParseError::WrongTokenToMove),
+        };
+
+        let turn = if to_move == player_token { Turn::Player } else { Turn::AI };
+
+        Ok(Game::with_board(board, player_token, turn))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    #[test]
+    fn boards() {
+        use super::*;
+
+        let board : Board = "X.O/.X./..O".parse().unwrap();
+        assert_eq!(board.tile(0, 0), Some(Tile::X));
+        assert_eq!(board.tile(2, 2), Some(Tile::O));
+        assert_eq!(board.to_string(), "X.O/.X./..O");
+
+        let board : Board = "..../..../..../....:3".parse().unwrap();
+        assert_eq!(board.dimensions(), (4, 4, 3));
+        assert_eq!(board.to_string(), "..../..../..../....:3");
+        assert_eq!(Board::with_size(15, 15, 5).unwrap().to_string().parse::<Board>().unwrap().dimensions(), (15, 15, 5));
+
+        assert_eq!("X.O/.X/..O".parse::<Board>(), Err(ParseError::UnevenRows));
+        assert_eq!("X.O/.Y./..O".parse::<Board>(), Err(ParseError::InvalidTile('Y')));
+        assert_eq!("X.O/.X./..O:x".parse::<Board>(), Err(ParseError::InvalidWinLength));
+        assert_eq!("X.O/.X./..O:4".parse::<Board>(), Err(ParseError::InvalidSize(GameError::InvalidSize { width: 3, height: 3, win_length: 4 })));
+    }
+
+    #[test]
+    fn impossible_boards() {
+        use super::*;
+
+        assert_eq!("XXO/.X./...".parse::<Board>(), Err(ParseError::PieceCount { x: 3, o: 1 }));
+        assert_eq!("XXX/OOO/...".parse::<Board>(), Err(ParseError::TooManyWinners));
+        assert_eq!("XXX/OO./OO.".parse::<Board>(), Err(ParseError::WinnerNotLast(Tile::X)));
+        assert_eq!("XXX/O.O/XXX".parse::<Board>(), Err(ParseError::PieceCount { x: 6, o: 2 }));
+        assert_eq!("XXX./OO.O/XXX./O.OO:3".parse::<Board>(), Err(ParseError::TooManyWinners));
+
+        // A single move can finish two lines at once
+        assert!("XXX/XOO/XOO".parse::<Board>().is_ok());
+    }
+
+    #[test]
+    fn games() {
+        use super::*;
+
+        let mut game : Game = "X../.O./... X O".parse().unwrap();
+        assert_eq!(game.turn(), Some(Turn::AI));
+        assert_eq!(game.player_token(), Tile::O);
+        assert_eq!(game.to_string(), "X../.O./... X O");
+
+        game.ai_move().unwrap();
+        assert_eq!(game.to_string(), "X.X/.O./... O O");
+
+        let game : Game = "XXX/OO./... - O".parse().unwrap();
+        assert_eq!(game.turn(), None);
+        assert_eq!(game.to_string(), "XXX/OO./... - O");
+
+        assert_eq!("X../.../... X O".parse::<Game>().unwrap_err(), ParseError::WrongTokenToMove);
+        assert_eq!("XXX/OO./... O O".parse::<Game>().unwrap_err(), ParseError::WrongTokenToMove);
+        assert_eq!("X../.../... - O".parse::<Game>().unwrap_err(), ParseError::WrongTokenToMove);
+        assert_eq!("X../.../... O Z".parse::<Game>().unwrap_err(), ParseError::InvalidToken);
+        assert_eq!("X../.../...".parse::<Game>().unwrap_err(), ParseError::WrongFieldCount);
+    }
+}
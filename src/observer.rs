@@ -0,0 +1,52 @@
+use crate::board::Position;
+use crate::strategy::Strategy;
+
+use std::sync::mpsc::Sender;
+
+/// Something the rule-based engine did while choosing a move
+#[derive(Debug, PartialEq, Eq, Clone, Copy)]
+pub enum Event {
+    /// A strategy is about to be checked
+    StageEntered(Strategy),
+    /// A strategy found a tile it could play, sent for every such tile before the tie break
+    /// picks one of them
+    CandidateFound { strategy: Strategy, position: Position },
+    /// The engine settled on a tile
+    MoveChosen { strategy: Strategy, position: Position },
+}
+
+/// Receives events from the rule-based engine, so callers decide whether and where they go.
+///
+/// Closures taking an `&Event` are observers, as are channel senders.
+///
+/// ```
+/// use tictactoe::engine::RuleBased;
+/// use tictactoe::{Event, Game, Strategy};
+/// use std::sync::mpsc::channel;
+///
+/// let (sender, receiver) = channel();
+/// let mut engine = RuleBased::new().with_observer(sender);
+///
+/// let mut game = Game::new();
+/// game.player_move(0, 0).unwrap();
+/// let position = game.ai_move_with(&mut engine).unwrap();
+///
+/// let events : Vec<Event> = receiver.try_iter().collect();
+/// assert_eq!(events.last(), Some(&Event::MoveChosen { strategy: Strategy::Center, position }));
+/// ```
+pub trait Observer {
+    fn on_event(&mut self, event: &Event);
+}
+
+impl<F: FnMut(&Event)> Observer for F {
+    fn on_event(&mut self, event: &Event) {
+        self(event)
+    }
+}
+
+impl Observer for Sender<Event> {
+    fn on_event(&mut self, event: &Event) {
+        // Nobody listening just means nobody wants the events
+        let _ = self.send(*event);
+    }
+}
@@ -0,0 +1,550 @@
+//! A text record of a whole game, for logs and bug reports.
+//!
+//! A record is a header of `[Name "value"]` lines followed by the moves:
+//!
+//! ```text
+//! [Player "Alice"]
+//! [Opponent "RuleBased"]
+//! [PlayerToken "X"]
+//! [FirstTurn "Player"]
+//! [Board "3x3:3"]
+//! [Result "Draw"]
+//! [Date "2026-10-18"]
+//!
+//! 1. a1 b2 2. c3 b1 3. b3 a3 4. c1 c2 5. a2
+//! ```
+//!
+//! Tiles are named by a column letter from `a` on the left and a row number from `1` at the
+//! top, so `a1` is `(0, 0)` and `c2` is `(2, 1)`. Columns after `z` carry on as `aa`, `ab` and
+//! so on. The number before each pair of moves is
+//! only there for readers and is skipped when parsing. The result is `Player` or `AI` for
+//! the side which won, `Draw`, or `*` when the game was still being played.
+//!
+//! Header values are written with `\"`, `\\` and `\n` in place of quotes, backslashes and
+//! line breaks, so any name can be kept.
+//!
+//! A game which started with tiles already claimed has a `[Setup "X../.O./..."]` header giving
+//! the starting board in the same notation as `Board`'s `Display`, and its moves follow on
+//! from there.
+//!
+//! Several records can be kept in one file, one after another. [`Record::parse_all`] reads
+//! them back, starting a new record at each header which follows a blank line or moves.
+
+use crate::board::{Board, Position};
+use crate::conditions::{GameStatus, Turn, Winner};
+use crate::error::GameError;
+use crate::game::Game;
+use crate::notation::parse_tiles;
+use crate::tile::Tile;
+
+use std::error::Error;
+use std::fmt;
+use std::str::FromStr;
+
+/// Reasons text can't be read as a game record, or replayed
+#[derive(Debug, PartialEq, Clone)]
+pub enum RecordError {
+    /// A header the record has to have is missing
+    MissingHeader(&'static str),
+    /// A header line or value which can't be read
+    InvalidHeader(String),
+    /// A word in the move list which isn't a tile name
+    InvalidTile(String),
+    /// The move with this number, counting from 1, couldn't be played
+    IllegalMove { number: usize, error: GameError },
+    /// The result in the header isn't how the moves end
+    ResultMismatch,
+}
+
+impl fmt::Display for RecordError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            RecordError::MissingHeader(name) => write!(f, "Missing {} header", name),
+            RecordError::InvalidHeader(header) => write!(f, "Can't read header {}", header),
+            RecordError::InvalidTile(tile) => write!(f, "'{}' is not a tile", tile),
+            RecordError::IllegalMove { number, error } => write!(f, "Move {} can't be played: {}", number, error),
+            RecordError::ResultMismatch => write!(f, "The moves don't end in the recorded result"),
+        }
+    }
+}
+
+impl Error for RecordError {}
+
+/// A whole game, with who played it, where it started and how it ended
+#[derive(Debug, Clone)]
+pub struct Record {
+    /// Name of the player
+    pub player: String,
+    /// Name of the engine the player faced
+    pub opponent: String,
+    pub player_token: Tile,
+    pub first_turn: Turn,
+    /// The board's width, height and win length
+    pub dimensions: (usize, usize, usize),
+    pub result: GameStatus,
+    pub date: String,
+    /// The board before the first move, when it didn't start empty
+    pub setup: Option<Board>,
+    pub moves: Vec<Position>,
+}
+
+impl Record {
+    /// Records `game`, along with the board it started from if any tiles were claimed
+    /// before the first move
+    pub fn new(game: &Game, player: &str, opponent: &str, date: &str) -> Record {
+        let mut start = game.board().clone();
+        for played in game.history() {
+            start.undo_move(played.position.0, played.position.1);
+        }
+
+        Record {
+            player: player.to_string(),
+            opponent: opponent.to_string(),
+            player_token: game.player_token(),
+            first_turn: game.history().first().map_or(game.turn().unwrap_or(Turn::Player), |first| first.turn),
+            dimensions: game.board().dimensions(),
+            result: game.result(),
+            date: date.to_string(),
+            setup: if start.filled_tiles() > 0 { Some(start) } else { None },
+            moves: game.history().iter().map(|played| played.position).collect(),
+        }
+    }
+
+    /// Reads every record in `text`, as written one after another by `Display`
+    pub fn parse_all(text: &str) -> Result<Vec<Record>, RecordError> {
+        let mut records = Vec::new();
+        let mut current = String::new();
+        let mut in_header = false;
+
+        for line in text.lines() {
+            let header = line.trim().starts_with('[');
+            if header && !in_header && !current.trim().is_empty() {
+                records.push(current.parse()?);
+                current.clear();
+            }
+
+            in_header = header;
+            current.push_str(line);
+            current.push('\n');
+        }
+
+        if !current.trim().is_empty() {
+            records.push(current.parse()?);
+        }
+
+        Ok(records)
+    }
+
+    /// Plays the moves again on a fresh game, checking they end in the recorded result
+    pub fn replay(&self) -> Result<Game, RecordError> {
+        let game = self.play_moves()?;
+
+        if result_name(game.result()) != result_name(self.result) {
+            return Err(RecordError::ResultMismatch)
+        }
+
+        Ok(game)
+    }
+
+    fn play_moves(&self) -> Result<Game, RecordError> {
+        let (width, height, win_length) = self.dimensions;
+        let board = match &self.setup {
+            Some(setup) if setup.dimensions() == self.dimensions => setup.clone(),
+            Some(setup) => return Err(RecordError::InvalidHeader(format!("Setup \"{}\"", setup))),
+            None => Board::with_size(width, height, win_length)
+                .map_err(|_| RecordError::InvalidHeader(format!("Board \"{}x{}:{}\"", width, height, win_length)))?,
+        };
+        let mut game = Game::with_board(board, self.player_token, self.first_turn);
+
+        for (index, &(x, y)) in self.moves.iter().enumerate() {
+            let played = match game.turn() {
+                Some(Turn::AI) => game.ai_move_at(x, y),
+                _ => game.player_move(x, y),
+            };
+
+            played.map_err(|error| RecordError::IllegalMove { number: index + 1, error })?;
+        }
+
+        Ok(game)
+    }
+}
+
+// A record only says who won, not which line they completed
+fn result_name(result: GameStatus) -> &'static str {
+    match result {
+        GameStatus::InProgress => "*",
+        GameStatus::Won { by: Winner::Player, .. } => "Player",
+        GameStatus::Won { by: Winner::AI, .. } => "AI",
+        GameStatus::Draw => "Draw",
+    }
+}
+
+/// The name of the tile at `(x, y)`, such as `b3` for `(1, 2)`
+pub fn tile_name((x, y): Position) -> String {
+    format!("{}{}", column_name(x), y + 1)
+}
+
+/// The letters naming column `x`, running `a` to `z` and then on to `aa`, `ab` and so on
+/// like a spreadsheet, so wide boards never run out of names
+pub fn column_name(x: usize) -> String {
+    let mut letters = Vec::new();
+    let mut remaining = x + 1;
+
+    while remaining > 0 {
+        remaining -= 1;
+        letters.push((b'a' + (remaining % 26) as u8) as char);
+        remaining /= 26;
+    }
+
+    letters.iter().rev().collect()
+}
+
+/// The tile named by `name`, such as `(1, 2)` for `b3`
+pub fn parse_tile_name(name: &str) -> Option<Position> {
+    let name = name.to_ascii_lowercase();
+    let digits = name.find(|c: char| !c.is_ascii_lowercase())?;
+    let (letters, row) = name.split_at(digits);
+    if letters.is_empty() {
+        return None
+    }
+
+    let column = letters.bytes().try_fold(0usize, |column, letter| {
+        column.checked_mul(26)?.checked_add((letter - b'a') as usize + 1)
+    })?;
+
+    let row : usize = row.parse().ok()?;
+    if row == 0 {
+        return None
+    }
+
+    Some((column - 1, row - 1))
+}
+
+impl fmt::Display for Record {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        let (width, height, win_length) = self.dimensions;
+
+        writeln!(f, "[Player \"{}\"]", escape(&self.player))?;
+        writeln!(f, "[Opponent \"{}\"]", escape(&self.opponent))?;
+        writeln!(f, "[PlayerToken \"{:?}\"]", self.player_token)?;
+        writeln!(f, "[FirstTurn \"{:?}\"]", self.first_turn)?;
+        writeln!(f, "[Board \"{}x{}:{}\"]", width, height, win_length)?;
+        if let Some(setup) = &self.setup {
+            writeln!(f, "[Setup \"{}\"]", setup)?;
+        }
+        writeln!(f, "[Result \"{}\"]", result_name(self.result))?;
+        writeln!(f, "[Date \"{}\"]", escape(&self.date))?;
+        writeln!(f)?;
+
+        for (index, &position) in self.moves.iter().enumerate() {
+            if index > 0 {
+                write!(f, " ")?;
+            }
+            if index % 2 == 0 {
+                write!(f, "{}. ", index / 2 + 1)?;
+            }
+            write!(f, "{}", tile_name(position))?;
+        }
+
+        writeln!(f)
+    }
+}
+
+/// Reads a record and replays it, so only records of games which could have been played
+/// are accepted
+impl FromStr for Record {
+    type Err = RecordError;
+
+    fn from_str(text: &str) -> Result<Record, RecordError> {
+        let mut player = None;
+        let mut opponent = None;
+        let mut player_token = None;
+        let mut first_turn = None;
+        let mut dimensions = None;
+        let mut result = None;
+        let mut date = None;
+        let mut setup = None;
+        let mut moves = Vec::new();
+
+        for line in text.lines().map(str::trim) {
+            if line.starts_with('[') {
+                let invalid = || RecordError::InvalidHeader(line.to_string());
+                let (name, value) = line.strip_prefix('[')
+                    .and_then(|line| line.strip_suffix(']'))
+                    .and_then(|line| line.split_once(' '))
+                    .ok_or_else(invalid)?;
+                let value = value.trim().strip_prefix('"').and_then(|value| value.strip_suffix('"'))
+                    .and_then(unescape)
+                    .ok_or_else(invalid)?;
+                let value = value.as_str();
+
+                match name {
+                    "Player" => player = Some(value.to_string()),
+                    "Opponent" => opponent = Some(value.to_string()),
+                    "PlayerToken" => player_token = Some(match value {
+                        "X" => Tile::X,
+                        "O" => Tile::O,
+                        _ => return Err(invalid()),
+                    }),
+                    "FirstTurn" => first_turn = Some(match value {
+                        "Player" => Turn::Player,
+                        "AI" => Turn::AI,
+                        _ => return Err(invalid()),
+                    }),
+                    "Board" => dimensions = Some(parse_dimensions(value).ok_or_else(invalid)?),
+                    "Result" => result = Some(match value {
+                        "*" | "Player" | "AI" | "Draw" => value.to_string(),
+                        _ => return Err(invalid()),
+                    }),
+                    "Date" => date = Some(value.to_string()),
+                    "Setup" => setup = Some(parse_tiles(value).map_err(|_| invalid())?),
+                    // Headers this version doesn't know about are left for other tools
+                    _ => (),
+                }
+            } else {
+                for word in line.split_whitespace().filter(|word| !word.ends_with('.')) {
+                    moves.push(parse_tile_name(word).ok_or_else(|| RecordError::InvalidTile(word.to_string()))?);
+                }
+            }
+        }
+
+        let mut record = Record {
+            player: player.ok_or(RecordError::MissingHeader("Player"))?,
+            opponent: opponent.ok_or(RecordError::MissingHeader("Opponent"))?,
+            player_token: player_token.ok_or(RecordError::MissingHeader("PlayerToken"))?,
+            first_turn: first_turn.ok_or(RecordError::MissingHeader("FirstTurn"))?,
+            dimensions: dimensions.or_else(|| setup.as_ref().map(Board::dimensions)).unwrap_or((3, 3, 3)),
+            result: GameStatus::InProgress,
+            date: date.unwrap_or_default(),
+            setup,
+            moves,
+        };
+
+        // Replayed so the winning line is filled in, and the result checked against the moves
+        let replayed = record.play_moves()?.result();
+        if result.ok_or(RecordError::MissingHeader("Result"))? != result_name(replayed) {
+            return Err(RecordError::ResultMismatch)
+        }
+
+        record.result = replayed;
+        Ok(record)
+    }
+}
+
+// A header value with the characters which would end it early written as escapes
+fn escape(value: &str) -> String {
+    let mut escaped = String::with_capacity(value.len());
+
+    for character in value.chars() {
+        match character {
+            '"' => escaped.push_str("\\\""),
+            '\\' => escaped.push_str("\\\\"),
+            '\n' => escaped.push_str("\\n"),
+            '\r' => escaped.push_str("\\r"),
+            _ => escaped.push(character),
+        }
+    }
+
+    escaped
+}
+
+// Undoes `escape`, or `None` if the value has a bare quote or an unknown escape
+fn unescape(value: &str) -> Option<String> {
+    let mut unescaped = String::with_capacity(value.len());
+    let mut characters = value.chars();
+
+    while let Some(character) = characters.next() {
+        match character {
+            '"' => return None,
+            '\\' => unescaped.push(match characters.next()? {
+                '"' => '"',
+                '\\' => '\\',
+                'n' => '\n',
+                'r' => '\r',
+                _ => return None,
+            }),
+            _ => unescaped.push(character),
+        }
+    }
+
+    Some(unescaped)
+}
+
+fn parse_dimensions(value: &str) -> Option<(usize, usize, usize)> {
+    let (size, win_length) = value.split_once(':')?;
+    let (width, height) = size.split_once('x')?;
+
+    Some((width.parse().ok()?, height.parse().ok()?, win_length.parse().ok()?))
+}
+
+#[cfg(test)]
+mod tests {
+    #[test]
+    fn tile_names() {
+        use super::*;
+
+        assert_eq!(tile_name((0, 0)), "a1");
+        assert_eq!(tile_name((2, 1)), "c2");
+        assert_eq!(parse_tile_name("c2"), Some((2, 1)));
+        assert_eq!(parse_tile_name("B3"), Some((1, 2)));
+        assert_eq!(parse_tile_name("o15"), Some((14, 14)));
+        assert_eq!(parse_tile_name("a0"), None);
+        assert_eq!(parse_tile_name("1a"), None);
+        assert_eq!(parse_tile_name(""), None);
+        assert_eq!(parse_tile_name("b"), None);
+
+        // Past z the columns carry on with two letters, then three
+        for &(x, name) in &[(25, "z1"), (26, "aa1"), (27, "ab1"), (51, "az1"), (52, "ba1"), (701, "zz1"), (702, "aaa1")] {
+            assert_eq!(tile_name((x, 0)), name);
+            assert_eq!(parse_tile_name(name), Some((x, 0)));
+        }
+        for x in 0..1000 {
+            assert_eq!(parse_tile_name(&tile_name((x, 7))), Some((x, 7)));
+        }
+        assert_eq!(parse_tile_name("zzzzzzzzzzzzzzzzzzzz1"), None);
+    }
+
+    #[test]
+    fn wide_boards() {
+        use super::*;
+
+        let board = Board::with_size(64, 2, 3).unwrap();
+        let mut game = Game::with_board(board, Tile::X, Turn::Player);
+        for &(x, y) in &[(30, 0), (0, 1), (31, 0), (63, 1), (29, 0)] {
+            match game.turn() {
+                Some(Turn::AI) => game.ai_move_at(x, y).unwrap(),
+                _ => game.player_move(x, y).unwrap(),
+            }
+        }
+
+        let record = Record::new(&game, "Alice", "Bob", "2026-10-18");
+        let text = record.to_string();
+        assert!(text.ends_with("1. ae1 a2 2. af1 bl2 3. ad1\n"), "{}", text);
+
+        let read : Record = text.parse().unwrap();
+        assert_eq!(read.moves, record.moves);
+        assert_eq!(read.replay().unwrap().board(), game.board());
+        assert!(matches!(read.result, GameStatus::Won { by: Winner::Player, .. }));
+    }
+
+    #[test]
+    fn write_and_read() {
+        use super::*;
+
+        let mut game = Game::with_options(Tile::X, Turn::Player);
+        while let Some(turn) = game.turn() {
+            match turn {
+                Turn::AI => {
+                    game.ai_move().unwrap();
+                },
+                Turn::Player => {
+                    let (x, y) = game.board().empty_tiles()[0];
+                    game.player_move(x, y).unwrap();
+                },
+            }
+        }
+
+        let record = Record::new(&game, "Alice", "RuleBased", "2026-10-18");
+        let text = record.to_string();
+        assert!(text.starts_with("[Player \"Alice\"]\n[Opponent \"RuleBased\"]\n[PlayerToken \"X\"]\n[FirstTurn \"Player\"]\n"));
+        assert!(text.ends_with("[Result \"AI\"]\n[Date \"2026-10-18\"]\n\n1. a1 b2 2. b1 c1 3. a2 a3\n"), "{}", text);
+
+        let read : Record = text.parse().unwrap();
+        assert_eq!(read.moves, record.moves);
+        assert_eq!(read.result, game.result());
+        assert_eq!(read.replay().unwrap().board(), game.board());
+    }
+
+    #[test]
+    fn several_records() {
+        use super::*;
+
+        let mut won = Game::with_options(Tile::X, Turn::Player);
+        for &(x, y) in &[(0, 0), (0, 1), (1, 0), (1, 1), (2, 0)] {
+            match won.turn() {
+                Some(Turn::AI) => won.ai_move_at(x, y).unwrap(),
+                _ => won.player_move(x, y).unwrap(),
+            }
+        }
+        let records = vec![
+            Record::new(&won, "Alice", "Bob", "2026-10-18"),
+            Record::new(&Game::new(), "Carol", "Dave", "2026-10-19"),
+            Record::new(&won, "Erin", "Frank", ""),
+        ];
+
+        let text = records.iter().map(Record::to_string).collect::<Vec<_>>().join("\n");
+        let read = Record::parse_all(&text).unwrap();
+        assert_eq!(read.len(), 3);
+        for (read, record) in read.iter().zip(&records) {
+            assert_eq!(read.to_string(), record.to_string());
+        }
+
+        assert_eq!(Record::parse_all("").unwrap().len(), 0);
+        assert!(Record::parse_all(&format!("{}\n[Player \"Alice\"]\n", text)).is_err());
+    }
+
+    #[test]
+    fn set_up_positions() {
+        use super::*;
+
+        // Two tiles claimed before the game began, which couldn't happen by taking turns
+        let board = crate::notation::parse_tiles("XX./.../...").unwrap();
+        let mut game = Game::with_board(board, Tile::O, Turn::Player);
+        game.player_move(2, 0).unwrap();
+        game.ai_move_at(1, 1).unwrap();
+
+        let record = Record::new(&game, "Alice", "Bob", "2026-10-18");
+        let text = record.to_string();
+        assert!(text.contains("[Board \"3x3:3\"]\n[Setup \"XX./.../...\"]\n"), "{}", text);
+        assert!(text.ends_with("1. c1 b2\n"), "{}", text);
+
+        let read : Record = text.parse().unwrap();
+        assert_eq!(read.setup.as_ref(), record.setup.as_ref());
+        assert_eq!(read.replay().unwrap().board(), game.board());
+
+        // Games from an empty board have no setup
+        assert_eq!(Record::new(&Game::new(), "Alice", "Bob", "").setup, None);
+        assert!(!Record::new(&Game::new(), "Alice", "Bob", "").to_string().contains("Setup"));
+
+        let mismatched = text.replace("[Board \"3x3:3\"]", "[Board \"4x4:3\"]");
+        assert_eq!(mismatched.parse::<Record>().unwrap_err(), RecordError::InvalidHeader("Setup \"XX./.../...\"".to_string()));
+    }
+
+    #[test]
+    fn escaped_headers() {
+        use super::*;
+
+        let mut record = Record::new(&Game::new(), "Al \"the pal\" \\ Smith\nJr", "Bob]", "18th \"Oct\"");
+        let text = record.to_string();
+        assert!(text.starts_with("[Player \"Al \\\"the pal\\\" \\\\ Smith\\nJr\"]\n[Opponent \"Bob]\"]\n"), "{}", text);
+
+        let read : Record = text.parse().unwrap();
+        assert_eq!((read.player.as_str(), read.opponent.as_str(), read.date.as_str()), ("Al \"the pal\" \\ Smith\nJr", "Bob]", "18th \"Oct\""));
+        assert_eq!(Record::parse_all(&format!("{}\n{}", text, text)).unwrap().len(), 2);
+
+        // Quotes and escapes which weren't written by `Display`
+        record.player = "Alice".to_string();
+        for bad in &["[Player \"A\"lice\"]", "[Player \"Al\\ice\"]", "[Player \"Alice\\\"]"] {
+            let text = record.to_string().replace("[Player \"Alice\"]", bad);
+            assert_eq!(text.parse::<Record>().unwrap_err(), RecordError::InvalidHeader(bad.to_string()));
+        }
+    }
+
+    #[test]
+    fn rejects_bad_records() {
+        use super::*;
+
+        let header = "[Player \"Alice\"]\n[Opponent \"RuleBased\"]\n[PlayerToken \"O\"]\n[FirstTurn \"AI\"]\n[Board \"3x3:3\"]\n";
+
+        let record : Record = format!("{}[Result \"AI\"]\n\n1. a1 b2 2. b1 c3 3. c1", header).parse().unwrap();
+        assert!(matches!(record.result, GameStatus::Won { by: Winner::AI, .. }));
+        assert_eq!(record.dimensions, (3, 3, 3));
+
+        assert_eq!(format!("{}[Result \"Draw\"]\n\n1. a1 b2 2. b1 c3 3. c1", header).parse::<Record>().unwrap_err(), RecordError::ResultMismatch);
+        assert_eq!(format!("{}[Result \"*\"]\n\n1. a1 a1", header).parse::<Record>().unwrap_err(),
+            RecordError::IllegalMove { number: 2, error: GameError::Occupied { x: 0, y: 0, by: Tile::X } });
+        assert_eq!(format!("{}[Result \"*\"]\n\n1. a1 z", header).parse::<Record>().unwrap_err(), RecordError::InvalidTile("z".to_string()));
+        assert_eq!("[Player \"Alice\"]\n\n1. a1".parse::<Record>().unwrap_err(), RecordError::MissingHeader("Opponent"));
+        assert_eq!(format!("{}[Result Draw]", header).parse::<Record>().unwrap_err(), RecordError::InvalidHeader("[Result Draw]".to_string()));
+    }
+}
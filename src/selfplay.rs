@@ -0,0 +1,310 @@
+use crate::conditions::{GameStatus, Turn, Winner};
+use crate::engine::{Difficulty, Engine, Handicapped, Mcts, Minimax, Perfect, RuleBased, TieBreak};
+use crate::game::Game;
+use crate::record::Record;
+use crate::tile::Tile;
+
+use std::fmt;
+use std::str::FromStr;
+use std::time::{SystemTime, UNIX_EPOCH};
+
+/// An engine which can be entered into a match
+#[derive(Debug, PartialEq, Eq, Hash, Copy, Clone)]
+pub enum Contender {
+    /// The rule-based engine with seeded tie breaks
+    RuleBased,
+    /// Claims a random empty tile every move
+    Random,
+    Minimax,
+    /// Monte Carlo tree search with its default budget
+    Mcts,
+    /// Looks every move up in the precomputed table
+    Table,
+    /// The rule-based engine playing at a difficulty level, so `perfect` is the rule-based
+    /// engine without handicaps rather than the table
+    Level(Difficulty),
+}
+
+impl Contender {
+    /// A fresh engine, with `seed` deciding any random choices it makes
+    pub fn engine(self, seed: u64) -> Box<dyn Engine> {
+        match self {
+            Contender::RuleBased => Box::new(RuleBased::with_tie_break(TieBreak::Random { seed })),
+            Contender::Random => Box::new(Handicapped::random(seed)),
+            Contender::Minimax => Box::new(Minimax::new()),
+            Contender::Mcts => Box::new(Mcts::new(seed)),
+            Contender::Table => Box::new(Perfect),
+            Contender::Level(difficulty) => Box::new(Handicapped::new(difficulty, seed)),
+        }
+    }
+}
+
+impl fmt::Display for Contender {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            Contender::RuleBased => write!(f, "rule"),
+            Contender::Random => write!(f, "random"),
+            Contender::Minimax => write!(f, "minimax"),
+            Contender::Mcts => write!(f, "mcts"),
+            Contender::Table => write!(f, "table"),
+            Contender::Level(difficulty) => write!(f, "{}", format!("{:?}", difficulty).to_lowercase()),
+        }
+    }
+}
+
+/// Reads the names written by `Display`: `rule`, `random`, `minimax`, `mcts`, `table`,
+/// `easy`, `medium`, `hard` or `perfect`
+impl FromStr for Contender {
+    type Err = String;
+
+    fn from_str(name: &str) -> Result<Contender, String> {
+        match name.to_lowercase().as_str() {
+            "rule" => Ok(Contender::RuleBased),
+            "random" => Ok(Contender::Random),
+            "minimax" => Ok(Contender::Minimax),
+            "mcts" => Ok(Contender::Mcts),
+            "table" => Ok(Contender::Table),
+            "easy" => Ok(Contender::Level(Difficulty::Easy)),
+            "medium" => Ok(Contender::Level(Difficulty::Medium)),
+            "hard" => Ok(Contender::Level(Difficulty::Hard)),
+            "perfect" => Ok(Contender::Level(Difficulty::Perfect)),
+            _ => Err(format!("Unknown engine '{}'", name)),
+        }
+    }
+}
+
+/// How a match between two engines went, counted from the first engine's side
+#[derive(Debug, Clone)]
+pub struct MatchReport {
+    pub first: Contender,
+    pub second: Contender,
+    pub wins: usize,
+    pub losses: usize,
+    pub draws: usize,
+    /// Games left unfinished because the first engine couldn't move, which aren't counted
+    /// as losses
+    pub first_forfeits: usize,
+    /// Games left unfinished because the second engine couldn't move, which aren't counted
+    /// as wins
+    pub second_forfeits: usize,
+    /// Moves made across every game
+    pub moves: usize,
+    /// Every game which somebody won, with the first engine recorded as the player
+    pub decisive: Vec<Record>,
+}
+
+impl MatchReport {
+    pub fn games(&self) -> usize {
+        self.wins + self.losses + self.draws + self.first_forfeits + self.second_forfeits
+    }
+
+    pub fn average_length(&self) -> f64 {
+        if self.games() == 0 {
+            0.0
+        } else {
+            self.moves as f64 / self.games() as f64
+        }
+    }
+
+    // Counts a finished game, with the first engine as the player
+    fn add(&mut self, game: &Game, date: &str) {
+        self.moves += game.history().len();
+
+        match game.result() {
+            GameStatus::Won { by, .. } => {
+                if by == Winner::Player {
+                    self.wins += 1;
+                } else {
+                    self.losses += 1;
+                }
+                self.decisive.push(Record::new(game, &self.first.to_string(), &self.second.to_string(), date));
+            },
+            GameStatus::Draw => self.draws += 1,
+            // Left unfinished because the engine to move couldn't
+            GameStatus::InProgress => match game.turn() {
+                Some(Turn::AI) => self.second_forfeits += 1,
+                _ => self.first_forfeits += 1,
+            },
+        }
+    }
+}
+
+impl fmt::Display for MatchReport {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(f, "{} vs {}: {} games, {} won {}, lost {}, drew {}, {:.1} moves on average",
+            self.first, self.second, self.games(), self.first, self.wins, self.losses, self.draws, self.average_length())?;
+
+        for &(engine, forfeits) in &[(self.first, self.first_forfeits), (self.second, self.second_forfeits)] {
+            if forfeits > 0 {
+                write!(f, ", {} forfeited {}", engine, forfeits)?;
+            }
+        }
+
+        Ok(())
+    }
+}
+
+/// Plays `games` games on the 3x3 board between two engines.
+///
+/// The first engine switches between X and O every game, and moves first in two games out
+/// of every four, so both engines see every combination. Game `n` seeds both engines with
+/// `seed + n`, so a match can always be played again move for move.
+///
+/// ```
+/// use tictactoe::selfplay::{play_match, Contender};
+///
+/// let report = play_match(Contender::RuleBased, Contender::Random, 20, 1);
+/// assert_eq!(report.games(), 20);
+/// assert_eq!(report.losses, 0);
+/// assert_eq!(report.decisive.len(), report.wins);
+/// ```
+pub fn play_match(first: Contender, second: Contender, games: usize, seed: u64) -> MatchReport {
+    let mut report = MatchReport {
+        first,
+        second,
+        wins: 0,
+        losses: 0,
+        draws: 0,
+        first_forfeits: 0,
+        second_forfeits: 0,
+        moves: 0,
+        decisive: Vec::new(),
+    };
+    let date = today();
+
+    for index in 0..games {
+        let game_seed = seed.wrapping_add(index as u64);
+        let token = if index % 2 == 0 { Tile::X } else { Tile::O };
+        let first_turn = if index % 4 < 2 { Turn::Player } else { Turn::AI };
+
+        let game = play_game(&mut first.engine(game_seed), &mut second.engine(game_seed), token, first_turn);
+        report.add(&game, &date);
+    }
+
+    report
+}
+
+// Today's date in UTC, written as the `Date` header of the records
+fn today() -> String {
+    date(SystemTime::now().duration_since(UNIX_EPOCH).map_or(0, |since| since.as_secs()))
+}
+
+// The UTC date `seconds` after the start of 1970
+fn date(seconds: u64) -> String {
+    let days = (seconds / 86_400) as i64;
+
+    // Days since 1970 to a date, counting in 400 year eras which start on the 1st of March
+    let shifted = days + 719_468;
+    let era = shifted.div_euclid(146_097);
+    let day_of_era = shifted.rem_euclid(146_097);
+    let year_of_era = (day_of_era - day_of_era / 1460 + day_of_era / 36_524 - day_of_era / 146_096) / 365;
+    let day_of_year = day_of_era - (365 * year_of_era + year_of_era / 4 - year_of_era / 100);
+    let shifted_month = (5 * day_of_year + 2) / 153;
+    let day = day_of_year - (153 * shifted_month + 2) / 5 + 1;
+    let month = if shifted_month < 10 { shifted_month + 3 } else { shifted_month - 9 };
+    let year = year_of_era + era * 400 + if month <= 2 { 1 } else { 0 };
+
+    format!("{:04}-{:02}-{:02}", year, month, day)
+}
+
+/// Plays one game with `player` moving for the player and `ai` for the AI, until it ends or
+/// an engine fails to move
+pub fn play_game<P: Engine, A: Engine>(player: &mut P, ai: &mut A, player_token: Tile, first_turn: Turn) -> Game {
+    let mut game = Game::with_options(player_token, first_turn);
+
+    while let Some(turn) = game.turn() {
+        let played = match turn {
+            Turn::AI => game.ai_move_with(ai).map(|_| ()),
+            Turn::Player => player.choose_move(&game).and_then(|(x, y)| game.player_move(x, y)),
+        };
+
+        if played.is_err() {
+            break
+        }
+    }
+
+    game
+}
+
+#[cfg(test)]
+mod tests {
+    #[test]
+    fn perfect_engines_draw() {
+        use super::*;
+
+        let report = play_match(Contender::RuleBased, Contender::Minimax, 8, 0);
+        assert_eq!((report.wins, report.losses, report.draws), (0, 0, 8));
+        assert_eq!(report.moves, 8 * 9);
+        assert!(report.decisive.is_empty());
+
+        let report = play_match(Contender::Table, "perfect".parse().unwrap(), 8, 0);
+        assert_eq!((report.wins, report.losses, report.draws), (0, 0, 8));
+    }
+
+    #[test]
+    fn matches_are_reproducible() {
+        use super::*;
+
+        let first = play_match(Contender::Level(Difficulty::Easy), Contender::Random, 40, 9);
+        let again = play_match(Contender::Level(Difficulty::Easy), Contender::Random, 40, 9);
+
+        assert_eq!((first.wins, first.losses, first.draws, first.moves), (again.wins, again.losses, again.draws, again.moves));
+        assert!(first.wins > 0 && first.losses > 0);
+
+        for record in &first.decisive {
+            let replayed = record.replay().unwrap();
+            assert!(matches!(replayed.result(), GameStatus::Won { .. }));
+            assert_eq!(record.to_string().parse::<Record>().unwrap().moves, record.moves);
+        }
+    }
+
+    #[test]
+    fn forfeits_are_counted_apart() {
+        use super::*;
+
+        struct Resigns;
+        impl Engine for Resigns {
+            fn choose_move(&mut self, _game: &Game) -> Result<crate::board::Position, crate::error::GameError> {
+                Err(crate::error::GameError::NoMoveAvailable)
+            }
+        }
+
+        let mut report = play_match(Contender::Minimax, Contender::Random, 0, 0);
+        report.add(&play_game(&mut Resigns, &mut RuleBased::new(), Tile::X, Turn::Player), "2026-10-18");
+        assert_eq!((report.wins, report.losses, report.draws, report.first_forfeits, report.second_forfeits), (0, 0, 0, 1, 0));
+        assert!(report.to_string().ends_with(", minimax forfeited 1"), "{}", report);
+
+        // The second engine failing after a move each is counted against it instead
+        let mut player = Minimax::new();
+        report.add(&play_game(&mut player, &mut Resigns, Tile::O, Turn::Player), "2026-10-18");
+        report.add(&play_game(&mut player, &mut Resigns, Tile::X, Turn::AI), "2026-10-18");
+        assert_eq!((report.first_forfeits, report.second_forfeits), (1, 2));
+        assert_eq!(report.games(), 3);
+        assert!(report.decisive.is_empty());
+        assert!(report.to_string().ends_with(", minimax forfeited 1, random forfeited 2"), "{}", report);
+    }
+
+    #[test]
+    fn records_are_dated() {
+        use super::*;
+
+        assert_eq!(date(0), "1970-01-01");
+        assert_eq!(date(951_868_800), "2000-03-01");
+        assert_eq!(date(1_709_164_800 + 86_399), "2024-02-29");
+        assert_eq!(date(1_792_281_600), "2026-10-18");
+
+        let report = play_match(Contender::Level(Difficulty::Easy), Contender::Random, 8, 2);
+        assert!(!report.decisive.is_empty());
+        assert!(report.decisive.iter().all(|record| record.date.len() == 10 && record.date.starts_with("20")));
+    }
+
+    #[test]
+    fn contender_names() {
+        use super::*;
+
+        for &name in &["rule", "random", "minimax", "mcts", "table", "easy", "medium", "hard", "perfect"] {
+            assert_eq!(name.parse::<Contender>().unwrap().to_string(), name);
+        }
+        assert!("grandmaster".parse::<Contender>().is_err());
+    }
+}
@@ -1,4 +1,9 @@
+#[cfg(feature = "serde")]
+use serde::{Deserialize, Serialize};
 
+/// The stages of the rule-based engine, in the order they are tried
+#[derive(Debug, PartialEq, Eq, Hash, Copy, Clone)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
 pub enum Strategy {
     Win,
     Block,
@@ -8,4 +13,23 @@ pub enum Strategy {
     OppositeCorner,
     EmptyCorner,
     EmptySide
-}
\ No newline at end of file
+}
+
+impl Strategy {
+    /// Every stage, in the order they are tried
+    pub const ALL : [Strategy; 8] = [
+        Strategy::Win,
+        Strategy::Block,
+        Strategy::Fork,
+        Strategy::BlockFork,
+        Strategy::Center,
+        Strategy::OppositeCorner,
+        Strategy::EmptyCorner,
+        Strategy::EmptySide,
+    ];
+
+    /// The stage tried after this one, or `None` for the last
+    pub fn next(self) -> Option<Strategy> {
+        Strategy::ALL.iter().skip_while(|&&stage| stage != self).nth(1).copied()
+    }
+}
@@ -0,0 +1,187 @@
+use crate::board::{Board, Position};
+use crate::tile::Tile;
+
+/// One of the eight ways to rotate or mirror a board onto itself. Rotating by a quarter turn
+/// or mirroring along a diagonal swaps the width and height, so only square boards have all
+/// eight; other boards have the four which keep their shape.
+#[derive(Debug, PartialEq, Eq, Hash, Copy, Clone)]
+pub enum Transform {
+    Identity,
+    /// A quarter turn clockwise
+    Rotate90,
+    Rotate180,
+    /// A quarter turn anticlockwise
+    Rotate270,
+    /// Swaps the left and right sides
+    MirrorHorizontal,
+    /// Swaps the top and bottom
+    MirrorVertical,
+    /// Mirrors along the top left to bottom right diagonal
+    MirrorDiagonal,
+    /// Mirrors along the top right to bottom left diagonal
+    MirrorAntiDiagonal,
+}
+
+impl Transform {
+    /// Every transform, starting with the identity
+    pub const ALL : [Transform; 8] = [
+        Transform::Identity,
+        Transform::Rotate90,
+        Transform::Rotate180,
+        Transform::Rotate270,
+        Transform::MirrorHorizontal,
+        Transform::MirrorVertical,
+        Transform::MirrorDiagonal,
+        Transform::MirrorAntiDiagonal,
+    ];
+
+    /// Whether the transform swaps the width and height of a board
+    pub fn swaps_axes(self) -> bool {
+        matches!(self, Transform::Rotate90 | Transform::Rotate270 | Transform::MirrorDiagonal | Transform::MirrorAntiDiagonal)
+    }
+
+    /// The transform which undoes this one
+    pub fn inverse(self) -> Transform {
+        match self {
+            Transform::Rotate90 => Transform::Rotate270,
+            Transform::Rotate270 => Transform::Rotate90,
+            other => other,
+        }
+    }
+
+    /// Where the tile at `(x, y)` on a `width` by `height` board ends up
+    pub fn apply(self, (x, y): Position, width: usize, height: usize) -> Position {
+        match self {
+            Transform::Identity => (x, y),
+            Transform::Rotate90 => (height - 1 - y, x),
+            Transform::Rotate180 => (width - 1 - x, height - 1 - y),
+            Transform::Rotate270 => (y, width - 1 - x),
+            Transform::MirrorHorizontal => (width - 1 - x, y),
+            Transform::MirrorVertical => (x, height - 1 - y),
+            Transform::MirrorDiagonal => (y, x),
+            Transform::MirrorAntiDiagonal => (height - 1 - y, width - 1 - x),
+        }
+    }
+}
+
+impl Board {
+    /// A copy of the board with every tile moved by `transform`
+    pub fn transformed(&self, transform: Transform) -> Board {
+        let (width, height, win_length) = self.dimensions();
+        // Square boards keep their shape either way, so they keep their lines and keys too
+        let mut board = if transform.swaps_axes() && width != height {
+            Board::with_size(height, width, win_length).expect("a board turned on its side is still valid")
+        } else {
+            self.cleared()
+        };
+
+        for y in 0..height {
+            for x in 0..width {
+                if let Some(tile) = self.tile(x, y) {
+                    let (new_x, new_y) = transform.apply((x, y), width, height);
+                    // Transforms map tiles one to one, so every tile is still empty
+                    let _ = board.make_move(new_x, new_y, tile);
+                }
+            }
+        }
+
+        board
+    }
+
+    /// The same position for every rotation or reflection of it which keeps the board's shape,
+    /// along with the transform which turns this board into it. Moves found on the canonical
+    /// board map back with the inverse transform.
+    ///
+    /// ```
+    /// use tictactoe::{Board, Tile};
+    ///
+    /// let mut board = Board::new();
+    /// board.make_move(2, 2, Tile::X).unwrap();
+    ///
+    /// let (canonical, transform) = board.canonical();
+    /// assert_eq!(canonical.tile(0, 0), Some(Tile::X));
+    ///
+    /// // The tile next to the corner on the canonical board, back on the original
+    /// assert_eq!(transform.inverse().apply((1, 0), 3, 3), (1, 2));
+    /// ```
+    pub fn canonical(&self) -> (Board, Transform) {
+        let (width, height) = (self.width(), self.height());
+
+        Transform::ALL.iter()
+            .filter(|transform| width == height || !transform.swaps_axes())
+            .map(|&transform| (self.transformed(transform), transform))
+            // The first of the smallest, so an already canonical board keeps the identity
+            .min_by_key(|(board, _)| tile_codes(board))
+            .expect("the identity always applies")
+    }
+}
+
+// The tiles row by row, with claimed tiles ordered before empty ones so the canonical
+// board gathers its tokens towards the top left
+fn tile_codes(board: &Board) -> Vec<u8> {
+    (0..board.height()).flat_map(|y| (0..board.width()).map(move |x| (x, y)))
+        .map(|(x, y)| match board.tile(x, y) {
+            None => 2,
+            Some(Tile::X) => 1,
+            Some(Tile::O) => 0,
+        })
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    #[test]
+    fn inverse_undoes_transform() {
+        use super::*;
+
+        for &transform in Transform::ALL.iter() {
+            let (width, height) = if transform.swaps_axes() { (4, 3) } else { (3, 4) };
+            let (new_width, new_height) = if transform.swaps_axes() { (height, width) } else { (width, height) };
+
+            for y in 0..height {
+                for x in 0..width {
+                    let moved = transform.apply((x, y), width, height);
+                    assert!(moved.0 < new_width && moved.1 < new_height);
+                    assert_eq!(transform.inverse().apply(moved, new_width, new_height), (x, y), "{:?}", transform);
+                }
+            }
+        }
+    }
+
+    #[test]
+    fn symmetric_positions_share_a_canonical_form() {
+        use super::*;
+
+        let board : Board = "X.O/.X./...".parse().unwrap();
+        let (canonical, _) = board.canonical();
+
+        for &transform in Transform::ALL.iter() {
+            let turned = board.transformed(transform);
+            // Turning a square board doesn't build its lines again
+            assert!(turned.shares_layout(&board));
+            let (turned_canonical, used) = turned.canonical();
+            assert_eq!(turned_canonical, canonical);
+            assert_eq!(turned.transformed(used), canonical);
+
+            // A move on the canonical board lands on the matching tile of each variant
+            let (x, y) = used.inverse().apply((2, 2), 3, 3);
+            assert!(turned.is_legal_move(x, y));
+        }
+
+        assert_eq!(board.transformed(Transform::Rotate90).to_string(), "..X/.X./..O");
+    }
+
+    #[test]
+    fn rectangular_boards_keep_their_shape() {
+        use super::*;
+
+        let board : Board = "X.../..../...O:3".parse().unwrap();
+        let (canonical, transform) = board.canonical();
+
+        assert_eq!(canonical.dimensions(), (4, 3, 3));
+        assert!(!transform.swaps_axes());
+        assert_eq!(board.transformed(Transform::Rotate90).dimensions(), (3, 4, 3));
+        assert!(!board.transformed(Transform::Rotate90).shares_layout(&board));
+        assert!(board.transformed(Transform::Rotate180).shares_layout(&board));
+    }
+}
@@ -1,6 +1,20 @@
+#[cfg(feature = "serde")]
+use serde::{Deserialize, Serialize};
 
+/// The token a side places on the board
 #[derive(Debug, PartialEq, Copy, Clone)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
 pub enum Tile {
     X,
     O,
-}
\ No newline at end of file
+}
+
+impl Tile {
+    /// The token used by the opposing side
+    pub fn other(self) -> Tile {
+        match self {
+            Tile::X => Tile::O,
+            Tile::O => Tile::X,
+        }
+    }
+}
@@ -0,0 +1,167 @@
+use crate::board::Position;
+use crate::conditions::{GameStatus, Turn, Winner};
+use crate::engine::{Engine, RuleBased};
+use crate::error::GameError;
+use crate::game::Game;
+use crate::tile::Tile;
+
+use std::fmt;
+use std::panic::{self, AssertUnwindSafe};
+
+/// How the engine failed along a line of play
+#[derive(Debug, PartialEq, Clone)]
+pub enum Failure {
+    /// The player completed a line
+    Lost,
+    /// The engine panicked while choosing a move
+    Panicked(String),
+    /// The engine chose a tile which can't be claimed
+    IllegalMove { x: usize, y: usize },
+    /// The engine didn't produce a move at all
+    NoMove(GameError),
+}
+
+/// A sequence of moves which makes the engine fail
+#[derive(Debug, PartialEq, Clone)]
+pub struct Counterexample {
+    pub player_token: Tile,
+    pub first_turn: Turn,
+    pub moves: Vec<(Turn, Position)>,
+    pub failure: Failure,
+}
+
+impl fmt::Display for Counterexample {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(f, "Player {:?}, AI {:?}, {:?} first:", self.player_token, self.player_token.other(), self.first_turn)?;
+        for (turn, (x, y)) in &self.moves {
+            write!(f, " {:?}({}, {})", turn, x, y)?;
+        }
+
+        match &self.failure {
+            Failure::Lost => write!(f, " => AI lost"),
+            Failure::Panicked(message) => write!(f, " => AI panicked: {}", message),
+            Failure::IllegalMove { x, y } => write!(f, " => AI chose illegal tile ({}, {})", x, y),
+            Failure::NoMove(error) => write!(f, " => AI found no move: {}", error),
+        }
+    }
+}
+
+/// The outcome of walking the whole game tree against the engine
+#[derive(Debug, Default, PartialEq, Clone)]
+pub struct Verification {
+    /// Number of finished games which were played out
+    pub games: usize,
+    pub counterexamples: Vec<Counterexample>,
+}
+
+impl Verification {
+    pub fn is_unbeatable(&self) -> bool {
+        self.counterexamples.is_empty()
+    }
+}
+
+/// Plays every possible sequence of player moves against the rule-based engine, for both
+/// tokens and with either side moving first, collecting each line where the engine fails.
+pub fn verify_unbeatable() -> Verification {
+    verify_engine(&mut RuleBased::new())
+}
+
+/// Plays every possible sequence of player moves against `engine` on the 3x3 board, for
+/// both tokens and with either side moving first, collecting each line where it fails.
+pub fn verify_engine<E: Engine>(engine: &mut E) -> Verification {
+    let mut verification = Verification::default();
+
+    for &player_token in &[Tile::X, Tile::O] {
+        for &first_turn in &[Turn::AI, Turn::Player] {
+            let game = Game::with_options(player_token, first_turn);
+            walk(&game, engine, first_turn, &mut Vec::new(), &mut verification);
+        }
+    }
+
+    verification
+}
+
+fn walk<E: Engine>(game: &Game, engine: &mut E, first_turn: Turn, moves: &mut Vec<(Turn, Position)>, verification: &mut Verification) {
+    let failure = match game.turn() {
+        None => {
+            verification.games += 1;
+
+            if let GameStatus::Won { by: Winner::Player, .. } = game.result() {
+                Some(Failure::Lost)
+            } else {
+                None
+            }
+        },
+        Some(Turn::AI) => {
+            let mut next = game.clone();
+
+            match panic::catch_unwind(AssertUnwindSafe(|| next.ai_move_with(engine))) {
+                Ok(Ok(position)) => {
+                    moves.push((Turn::AI, position));
+                    walk(&next, engine, first_turn, moves, verification);
+                    moves.pop();
+                    None
+                },
+                Ok(Err(GameError::Occupied { x, y, .. })) | Ok(Err(GameError::OutOfBounds { x, y })) => {
+                    Some(Failure::IllegalMove { x, y })
+                },
+                Ok(Err(error)) => Some(Failure::NoMove(error)),
+                Err(payload) => {
+                    let message = payload.downcast_ref::<&str>().map(|message| message.to_string())
+                        .or_else(|| payload.downcast_ref::<String>().cloned())
+                        .unwrap_or_default();
+                    Some(Failure::Panicked(message))
+                },
+            }
+        },
+        Some(Turn::Player) => {
+            for position in game.board().empty_tiles() {
+                let mut next = game.clone();
+                // Only empty tiles are tried, so the move can't be rejected
+                let _ = next.player_move(position.0, position.1);
+
+                moves.push((Turn::Player, position));
+                walk(&next, engine, first_turn, moves, verification);
+                moves.pop();
+            }
+            None
+        },
+    };
+
+    if let Some(failure) = failure {
+        verification.counterexamples.push(Counterexample {
+            player_token: game.player_token(),
+            first_turn,
+            moves: moves.clone(),
+            failure,
+        });
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    #[test]
+    fn engine_is_unbeatable() {
+        use super::*;
+
+        let verification = verify_unbeatable();
+
+        for counterexample in &verification.counterexamples {
+            println!("{}", counterexample);
+        }
+
+        assert!(verification.games > 0);
+        assert!(verification.is_unbeatable(), "{} losing lines found", verification.counterexamples.len());
+    }
+
+    #[test]
+    fn random_tie_breaks_are_unbeatable() {
+        use super::*;
+        use crate::engine::TieBreak;
+
+        for seed in 0..4 {
+            let verification = verify_engine(&mut RuleBased::with_tie_break(TieBreak::Random { seed }));
+            assert!(verification.is_unbeatable(), "seed {} lost: {}", seed, verification.counterexamples[0]);
+        }
+    }
+}